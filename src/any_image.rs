@@ -0,0 +1,80 @@
+use image::DynamicImage;
+
+use crate::methods::{Image, Image16, ImageF32};
+
+/// Image in the precision it was decoded with
+///
+/// 8-bit inputs stay 8-bit, 16-bit PNG and TIFF stay 16-bit
+/// and HDR or OpenExr stay float, so operations don't lose precision of the source
+#[derive(Debug, Clone)]
+pub enum AnyImage {
+    Rgba8(Image),
+    Rgba16(Image16),
+    Rgba32F(ImageF32),
+}
+
+impl AnyImage {
+    /// Bits per channel of the image
+    pub fn bit_depth(&self) -> u8 {
+        match self {
+            AnyImage::Rgba8(_) => 8,
+            AnyImage::Rgba16(_) => 16,
+            AnyImage::Rgba32F(_) => 32,
+        }
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        match self {
+            AnyImage::Rgba8(img) => img.dimensions(),
+            AnyImage::Rgba16(img) => img.dimensions(),
+            AnyImage::Rgba32F(img) => img.dimensions(),
+        }
+    }
+}
+
+impl From<DynamicImage> for AnyImage {
+    fn from(img: DynamicImage) -> Self {
+        match img {
+            DynamicImage::ImageRgba8(img) => AnyImage::Rgba8(img),
+            DynamicImage::ImageLuma16(_)
+            | DynamicImage::ImageLumaA16(_)
+            | DynamicImage::ImageRgb16(_)
+            | DynamicImage::ImageRgba16(_) => AnyImage::Rgba16(img.into_rgba16()),
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => {
+                AnyImage::Rgba32F(img.into_rgba32f())
+            }
+            img => AnyImage::Rgba8(img.into_rgba8()),
+        }
+    }
+}
+
+impl From<AnyImage> for DynamicImage {
+    fn from(img: AnyImage) -> Self {
+        match img {
+            AnyImage::Rgba8(img) => DynamicImage::ImageRgba8(img),
+            AnyImage::Rgba16(img) => DynamicImage::ImageRgba16(img),
+            AnyImage::Rgba32F(img) => DynamicImage::ImageRgba32F(img),
+        }
+    }
+}
+
+/// Applies a generic operation to the image whatever its precision is
+///
+/// ```
+/// use climp::{map_image, mirror, AnyImage, Image16};
+///
+/// let img = AnyImage::Rgba16(Image16::new(4, 2));
+/// let mirrored = map_image!(&img, |buf| mirror(buf));
+///
+/// assert_eq!(mirrored.bit_depth(), 16);
+/// ```
+#[macro_export]
+macro_rules! map_image {
+    ($img:expr, |$buf:ident| $body:expr) => {
+        match $img {
+            $crate::AnyImage::Rgba8($buf) => $crate::AnyImage::Rgba8($body),
+            $crate::AnyImage::Rgba16($buf) => $crate::AnyImage::Rgba16($body),
+            $crate::AnyImage::Rgba32F($buf) => $crate::AnyImage::Rgba32F($body),
+        }
+    };
+}
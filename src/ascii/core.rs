@@ -16,6 +16,16 @@ pub fn render_to_file<P1: AsRef<Path>, P2: AsRef<Path>>(
     Ok(())
 }
 
+pub fn render_image_to_file<P: AsRef<Path>>(
+    image: &DynamicImage,
+    output_path: P,
+    options: &RenderOptions<'_>,
+) -> ImageResult<()> {
+    let mut output_file = io::BufWriter::new(std::fs::File::create(output_path)?);
+    render_image(image, &mut output_file, options)?;
+    Ok(())
+}
+
 pub fn render<P: AsRef<Path> + AsRef<str>>(
     path: P,
     to: &mut impl io::Write,
@@ -35,7 +45,6 @@ pub fn render_image(
     Ok(())
 }
 
-#[allow(dead_code)]
 pub fn render_to<P: AsRef<Path> + AsRef<str>>(
     path: P,
     buffer: &mut String,
@@ -47,7 +56,6 @@ pub fn render_to<P: AsRef<Path> + AsRef<str>>(
     Ok(())
 }
 
-#[allow(dead_code)]
 pub fn render_image_to(
     image: &DynamicImage,
     buffer: &mut String,
@@ -25,7 +25,8 @@ impl ImageRenderer<'_> {
     }
 
     fn get_grayscale(&self, pixel: &Rgba<u8>) -> f64 {
-        ((pixel[0] as f64 * 0.299) + (pixel[1] as f64 * 0.587) + (pixel[2] as f64 * 0.114)) / 255.0
+        let rgb = [0, 1, 2].map(|i| pixel[i] as f32 / 255.0);
+        self.options.luminance.of(rgb) as f64
     }
 }
 
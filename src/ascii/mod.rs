@@ -1,8 +1,11 @@
-mod charsets;
+pub mod charsets;
 mod core;
 mod image_renderer;
 mod renderer;
 
 pub use crate::ascii::charsets::from_str;
-pub use crate::ascii::core::{render, render_to_file};
-pub use crate::ascii::renderer::RenderOptions;
+pub use crate::ascii::core::{
+    render, render_image, render_image_to, render_image_to_file, render_to, render_to_file,
+};
+pub use crate::ascii::image_renderer::ImageRenderer;
+pub use crate::ascii::renderer::{RenderOptions, Renderer};
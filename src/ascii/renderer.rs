@@ -1,6 +1,7 @@
 use std::io;
 
 use super::charsets;
+use crate::luminance::Luminance;
 
 #[derive(Clone, Debug)]
 pub struct RenderOptions<'a> {
@@ -9,9 +10,9 @@ pub struct RenderOptions<'a> {
     pub colored: bool,
     pub invert: bool,
     pub charset: &'a [&'a str],
+    pub luminance: Luminance,
 }
 
-#[allow(dead_code)]
 impl<'a> RenderOptions<'a> {
     /// Create a new RenderOptions with default values.
     pub fn new() -> Self {
@@ -49,6 +50,12 @@ impl<'a> RenderOptions<'a> {
         self.charset = charset;
         self
     }
+
+    /// Set how the brightness of a pixel picking its character is computed.
+    pub fn luminance(mut self, luminance: Luminance) -> Self {
+        self.luminance = luminance;
+        self
+    }
 }
 
 impl Default for RenderOptions<'_> {
@@ -59,6 +66,7 @@ impl Default for RenderOptions<'_> {
             colored: false,
             invert: false,
             charset: charsets::DEFAULT,
+            luminance: Luminance::default(),
         }
     }
 }
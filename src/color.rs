@@ -0,0 +1,35 @@
+use image::Rgba;
+
+/// Parses a color given by its name or as hex `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa`
+///
+/// `#` is optional, names are `black`, `white`, `gray`, `red`, `green`, `blue` and `transparent`
+pub fn parse_color(value: &str) -> Result<Rgba<u8>, String> {
+    let color = match value.to_ascii_lowercase().as_str() {
+        "black" => Rgba([0, 0, 0, 255]),
+        "white" => Rgba([255, 255, 255, 255]),
+        "gray" | "grey" => Rgba([128, 128, 128, 255]),
+        "red" => Rgba([255, 0, 0, 255]),
+        "green" => Rgba([0, 255, 0, 255]),
+        "blue" => Rgba([0, 0, 255, 255]),
+        "transparent" => Rgba([0, 0, 0, 0]),
+        hex => parse_hex(hex.trim_start_matches('#'))
+            .ok_or_else(|| format!("`{value}` is neither a color name nor a hex color"))?,
+    };
+    Ok(color)
+}
+
+fn parse_hex(hex: &str) -> Option<Rgba<u8>> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|d| d * 17);
+    let byte = |i: usize| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok();
+
+    match hex.len() {
+        3 => Some(Rgba([digit(0)?, digit(1)?, digit(2)?, 255])),
+        4 => Some(Rgba([digit(0)?, digit(1)?, digit(2)?, digit(3)?])),
+        6 => Some(Rgba([byte(0)?, byte(1)?, byte(2)?, 255])),
+        8 => Some(Rgba([byte(0)?, byte(1)?, byte(2)?, byte(3)?])),
+        _ => None,
+    }
+}
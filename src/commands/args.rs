@@ -1,5 +1,7 @@
 use std::path::PathBuf;
 
+use super::output::OutputOptions;
+
 /// Command line arguments that can be parsed from the command line.
 ///
 /// Implemented in @flag_matcher.
@@ -8,19 +10,17 @@ use std::path::PathBuf;
 #[derive(Debug, Default)]
 pub struct Args {
     filepath: PathBuf,
-    radius: Option<u32>,
-    pixel: Option<u32>,
-    resize: Option<u32>,
     file_ext: Option<String>,
     output_name: PathBuf,
-    output_ext: Option<String>,
     width: Option<u32>,
     height: Option<u32>,
     colored: bool,
     invert: bool,
     charset: String,
-    threshold: Option<f32>,
     verbose_only: bool,
+    recursive: bool,
+    out_dir: Option<PathBuf>,
+    output_options: OutputOptions,
 }
 
 #[allow(dead_code)]
@@ -33,18 +33,6 @@ impl Args {
         &self.filepath
     }
 
-    pub fn get_radius(&self) -> Option<u32> {
-        self.radius
-    }
-
-    pub fn get_pixel(&self) -> Option<u32> {
-        self.pixel
-    }
-
-    pub fn get_resize(&self) -> Option<u32> {
-        self.resize
-    }
-
     pub fn get_file_ext(&self) -> Option<&String> {
         self.file_ext.as_ref()
     }
@@ -69,10 +57,6 @@ impl Args {
         &self.charset
     }
 
-    pub fn get_threshold(&self) -> Option<f32> {
-        self.threshold
-    }
-
     pub fn get_verbose_only(&self) -> bool {
         self.verbose_only
     }
@@ -81,24 +65,20 @@ impl Args {
         &self.output_name
     }
 
-    pub fn get_output_ext(&self) -> Option<&String> {
-        self.output_ext.as_ref()
-    }    
-
-    pub fn set_filepath(&mut self, filepath: PathBuf) {
-        self.filepath = filepath;
+    pub fn is_recursive(&self) -> bool {
+        self.recursive
     }
 
-    pub fn set_radius(&mut self, radius: Option<u32>) {
-        self.radius = radius;
+    pub fn get_out_dir(&self) -> Option<&PathBuf> {
+        self.out_dir.as_ref()
     }
 
-    pub fn set_pixel(&mut self, pixel: Option<u32>) {
-        self.pixel = pixel;
+    pub fn get_output_options(&self) -> &OutputOptions {
+        &self.output_options
     }
 
-    pub fn set_resize(&mut self, resize: Option<u32>) {
-        self.resize = resize;
+    pub fn set_filepath(&mut self, filepath: PathBuf) {
+        self.filepath = filepath;
     }
 
     pub fn set_file_ext(&mut self, file_ext: Option<String>) {
@@ -125,10 +105,6 @@ impl Args {
         self.charset = charset;
     }
 
-    pub fn set_threshold(&mut self, threshold: Option<f32>) {
-        self.threshold = threshold;
-    }
-
     pub fn set_verbose_only(&mut self, verbose_only: bool) {
         self.verbose_only = verbose_only;
     }
@@ -137,8 +113,15 @@ impl Args {
         self.output_name = output_name;
     }
 
-    pub fn set_output_ext(&mut self, output_ext: Option<String>) {
-        self.output_ext = output_ext;
+    pub fn set_recursive(&mut self, recursive: bool) {
+        self.recursive = recursive;
+    }
+
+    pub fn set_out_dir(&mut self, out_dir: Option<PathBuf>) {
+        self.out_dir = out_dir;
     }
 
+    pub fn set_output_options(&mut self, output_options: OutputOptions) {
+        self.output_options = output_options;
+    }
 }
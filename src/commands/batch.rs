@@ -0,0 +1,88 @@
+use climp::ClimpError;
+use image::ImageFormat;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Single file picked up for processing
+///
+/// `relative` is the path the output keeps inside of `--out-dir`:
+/// the file name for plain paths and the path below the walked directory otherwise
+#[derive(Debug, Clone)]
+pub struct Input {
+    pub path: PathBuf,
+    pub relative: PathBuf,
+}
+
+/// Expands file paths, glob patterns and directories into a list of input files
+///
+/// Directories are scanned for files with a known image extension,
+/// subdirectories are only entered when `recursive` is set.
+///
+/// Patterns which can't be expanded are returned as errors alongside the found inputs
+/// so that one bad pattern doesn't abort the whole batch
+pub fn collect_inputs(patterns: &[PathBuf], recursive: bool) -> (Vec<Input>, Vec<ClimpError>) {
+    let mut inputs = Vec::new();
+    let mut errors = Vec::new();
+
+    for pattern in patterns {
+        if pattern.is_dir() {
+            if let Err(e) = walk_dir(pattern, pattern, recursive, &mut inputs) {
+                errors.push(ClimpError::Io(pattern.clone(), e));
+            }
+        } else if pattern.exists() || !is_glob(pattern) {
+            inputs.push(Input {
+                path: pattern.clone(),
+                relative: PathBuf::from(pattern.file_name().unwrap_or_default()),
+            });
+        } else {
+            match glob::glob(&pattern.to_string_lossy()) {
+                Ok(paths) => {
+                    let before = inputs.len();
+                    for path in paths.flatten().filter(|p| p.is_file()) {
+                        inputs.push(Input {
+                            relative: PathBuf::from(path.file_name().unwrap_or_default()),
+                            path,
+                        });
+                    }
+                    if inputs.len() == before {
+                        errors.push(ClimpError::InvalidArgument(format!(
+                            "no files match {pattern:?}"
+                        )));
+                    }
+                }
+                Err(e) => errors.push(ClimpError::InvalidArgument(format!(
+                    "{pattern:?} is not a valid glob: {e}"
+                ))),
+            }
+        }
+    }
+
+    (inputs, errors)
+}
+
+fn is_glob(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?', '['])
+}
+
+fn walk_dir(root: &Path, dir: &Path, recursive: bool, inputs: &mut Vec<Input>) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            if recursive {
+                walk_dir(root, &path, recursive, inputs)?;
+            }
+        } else if ImageFormat::from_path(&path).is_ok() {
+            inputs.push(Input {
+                relative: path.strip_prefix(root).unwrap_or(&path).to_path_buf(),
+                path,
+            });
+        }
+    }
+
+    Ok(())
+}
@@ -1,109 +1,249 @@
-use clap::{arg, command, value_parser, ArgAction, ArgMatches, Command};
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::error::ErrorKind;
+use clap::{arg, command, crate_name, value_parser, Arg, ArgAction, ArgMatches, Command};
+use climp::palette::PaletteMethod;
+use climp::ToneMap;
 use std::path::PathBuf;
 
+use super::operation::{clahe_args, luminance_arg};
+use super::operations::{find, OPERATIONS};
+use super::output::{parse_format, Compression, PngFilter};
+use super::palette::Report;
+
+/// Subcommands which produce something other than an image, so they always end the chain
+pub const TERMINAL: &[&str] = &["ascii", "palette"];
+
 /// Creates command line arguments
 ///
 /// Returns ArgMatches container for parse results
+///
+/// `filepath` can only be omitted for `run`, which takes inputs from the recipe,
+/// and for `palette` given its own, as in `climp palette image.jpg`
 pub fn make_commands() -> ArgMatches {
-    command!() // requires `cargo` feature
+    let mut command = command!() // requires `cargo` feature
+        .subcommand_precedence_over_arg(true)
+        .arg(
+            arg!([filepath] ... "File paths, globs or directories of images you want to edit, - reads stdin")
+                .value_parser(value_parser!(PathBuf))
+                .action(ArgAction::Append),
+        )
         .arg(
-            arg!([filepath] "File path to image you want to edit")
-                .required(true)
-                .value_parser(value_parser!(PathBuf)),
+            arg!(-R --recursive "Look for images in subdirectories of the given directories")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            arg!(--"out-dir" <DIR> "Directory to write outputs into, keeping their file names")
+                .value_parser(value_parser!(PathBuf))
+                .action(ArgAction::Set),
         )
         .arg(
             arg!(
                 -o --output <filepath> "Output filepath"
             )
             .value_parser(value_parser!(PathBuf))
-            .help("Can be set to one of ImageFormat's values: Png, Jpeg, Gif, WebP, Pnm, Tiff, Tga, Dds,  Bmp, Ico, Hdr, OpenExr, Farbfeld, Avif, Qoi. But \x1b[31mascii\x1b[0m command produces files without any extension")
+            .help("Format is taken from the extension, can be set to one of ImageFormat's values: Png, Jpeg, Gif, WebP, Pnm, Tiff, Tga, Bmp, Ico, Hdr, OpenExr, Farbfeld, Qoi. Dds and Avif can only be read. But \x1b[31mascii\x1b[0m command produces files without any extension and \x1b[31mpalette\x1b[0m ones with the extension of its report. Use - to write to stdout")
             .action(ArgAction::Set),
         )
+        .args(output_args())
+        .subcommands(operations())
         .subcommand(
-            Command::new("pixelate")
-                .about("Pixelate the image with a given pixel size")
-                .arg_required_else_help(true)
+            Command::new("run")
+                .about("Run a TOML or JSON recipe describing inputs, operations and output")
                 .arg(
-                    arg!(-p --pixel_size <VALUE>)
-                        .required(true)
-                        .value_parser(value_parser!(u32))
-                        .action(ArgAction::Set),
+                    arg!(<recipe> "Recipe file, inputs and output options given to climp override it")
+                        .value_parser(value_parser!(PathBuf)),
                 ),
+        );
+
+    let matches = command.get_matches_mut();
+    let has_inputs = matches.contains_id("filepath")
+        || matches
+            .subcommand_matches("palette")
+            .is_some_and(|m| m.contains_id("filepath"));
+    if matches.subcommand_name() != Some("run") && !has_inputs {
+        command
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "the following required arguments were not provided:\n  <filepath>...",
+            )
+            .exit();
+    }
+    matches
+}
+
+/// Creates encoder options of the output
+///
+/// Recipes parse their `[output]` table with the same arguments
+pub fn output_args() -> Vec<Arg> {
+    vec![
+        arg!(--format <FORMAT> "Output format by its name or extension, e.g. png or jpg. Overrides the output extension, required for -o -")
+            .value_parser(parse_format)
+            .action(ArgAction::Set),
+        arg!(--quality <VALUE> "Jpeg and lossy WebP quality, 75 by default")
+            .value_parser(value_parser!(u8).range(1..=100))
+            .action(ArgAction::Set),
+        arg!(--compression <LEVEL> "Png compression level")
+            .value_parser(value_parser!(Compression))
+            .action(ArgAction::Set),
+        arg!(--png_filter <FILTER> "Png scanline filter, adaptive by default")
+            .value_parser(value_parser!(PngFilter))
+            .action(ArgAction::Set),
+        arg!(--lossy "Write lossy WebP of --quality instead of the lossless one")
+            .action(ArgAction::SetTrue),
+        arg!(--tone_map <OPERATOR> "How HDR highlights are brought into outputs with less than 32 bits: clamp (default), reinhard or aces")
+            .value_parser(
+                PossibleValuesParser::new(["clamp", "reinhard", "aces"])
+                    .map(|s| s.parse::<ToneMap>().unwrap()),
+            )
+            .action(ArgAction::Set),
+        arg!(--bit_depth <BITS> "Bits per channel: 8 or 16 for Png and Tiff, 16 for Farbfeld, 32 for Hdr and OpenExr, 8 for others. Keeps the input precision if the format allows by default")
+            .value_parser(value_parser!(u8))
+            .action(ArgAction::Set),
+    ]
+    .into_iter()
+    .map(|arg| arg.help_heading("Output options"))
+    .collect()
+}
+
+/// Creates the subcommands from the operations registry, `ascii` and `palette`
+///
+/// Every operation except the `TERMINAL` ones accepts further operations after its own
+/// arguments, so `rotate grayscale blur -r 3` is parsed as a chain of three.
+fn operations() -> Vec<Command> {
+    OPERATIONS
+        .iter()
+        .map(|op| chainable(op.command()))
+        .chain([ascii(), palette()])
+        .collect()
+}
+
+/// Creates subcommand of a single operation, without the rest of the chain
+pub fn step_command(name: &str) -> Option<Command> {
+    match name {
+        "ascii" => Some(ascii()),
+        "palette" => Some(palette()),
+        _ => find(name).map(|op| op.command()),
+    }
+}
+
+/// Creates `ascii` subcommand, it renders text instead of an image so it always ends the chain
+fn ascii() -> Command {
+    Command::new("ascii")
+        .about("Render image as an ASCII art with a given charset")
+        .arg(
+            arg!(--width <VALUE>)
+                .value_parser(value_parser!(u32))
+                .action(ArgAction::Set),
         )
-        .subcommand(
-            Command::new("blur")
-                .about("Blur the image with a given radius")
-                .arg_required_else_help(true)
-                .arg(
-                    arg!(-r --blur_radius <VALUE>)
-                        .required(true)
-                        .value_parser(value_parser!(u32))
-                        .action(ArgAction::Set),
-                ),
+        .arg(
+            arg!(--height <VALUE>)
+                .value_parser(value_parser!(u32))
+                .action(ArgAction::Set),
         )
-        .subcommand(Command::new("mirror").about("Mirror the image"))
-        .subcommand(Command::new("flip_vertical").about("Flip the image vertically"))
-        .subcommand(Command::new("rotate").about("Rotate an image 90 degrees clockwise"))
-        .subcommand(Command::new("grayscale").about("Make the image grayscale"))
-        .subcommand(
-            Command::new("monochrome_ugly")
-                .about("Make the image monochrome")
-                .arg(
-                    arg!(-t --threshold <VALUE>)
-                        .default_value("128.0")
-                        .value_parser(value_parser!(f32))
-                        .action(ArgAction::Set),
-                ),
+        .arg(
+            arg!(--colored <BOOL>)
+                .value_parser(value_parser!(bool))
+                .action(ArgAction::SetTrue),
         )
-        .subcommand(
-            Command::new("scale")
-                .about("Scale the image")
-                .arg_required_else_help(true)
-                .arg(
-                    arg!(-s --scale <VALUE>)
-                        .required(true)
-                        .value_parser(value_parser!(u32))
-                        .action(ArgAction::Set),
-                ),
+        .arg(
+            arg!(--invert <BOOL>)
+                .value_parser(value_parser!(bool))
+                .action(ArgAction::SetTrue),
         )
-        .subcommand(
-            Command::new("ascii")
-                .about("Render image as an ASCII art with a given charset")
-                .arg(
-                    arg!(--width <VALUE>)
-                        .value_parser(value_parser!(u32))
-                        .action(ArgAction::Set),
-                )
-                .arg(
-                    arg!(--height <VALUE>)
-                        .value_parser(value_parser!(u32))
-                        .action(ArgAction::Set),
-                )
-                .arg(
-                    arg!(--colored <BOOL>)
-                        .value_parser(value_parser!(bool))
-                        .action(ArgAction::SetTrue),
-                )
-                .arg(
-                    arg!(--invert <BOOL>)
-                        .value_parser(value_parser!(bool))
-                        .action(ArgAction::SetTrue),
-                )
-                .arg(
-                    arg!(--charset <SET>)
-                        .value_parser(value_parser!(String))
-                        .action(ArgAction::Set),
-                ).arg(
-                    arg!(-v --verbose_only <BOOL>)
-                        .value_parser(value_parser!(bool))
-                        .action(ArgAction::SetTrue),
-                ),
+        .arg(
+            arg!(--charset <SET>)
+                .value_parser(value_parser!(String))
+                .action(ArgAction::Set),
         )
-        .subcommand(
-            Command::new("curse").about("Curse the image")                
+        .arg(
+            arg!(-v --verbose_only <BOOL>)
+                .value_parser(value_parser!(bool))
+                .action(ArgAction::SetTrue),
         )
-        .subcommand(
-            Command::new("zxc").about("Ultimate zxc dead inside the image")                
+        .arg(
+            arg!(--equalize <METHOD> "Equalize luminance before rendering, global or clahe for tile by tile equalization")
+                .value_parser(["global", "clahe"])
+                .action(ArgAction::Set),
+        )
+        .arg(luminance_arg())
+        .args(clahe_args())
+}
+
+/// Creates `palette` subcommand, it reports dominant colors instead of an image so it always ends the chain
+///
+/// Takes input paths of its own, so `climp palette image.jpg` reads like the other commands
+fn palette() -> Command {
+    Command::new("palette")
+        .about("Report dominant colors of the image with their proportions")
+        .arg(
+            arg!([filepath] ... "File paths, globs or directories of images, same as the ones before the subcommand")
+                .value_parser(value_parser!(PathBuf))
+                .action(ArgAction::Append),
+        )
+        .arg(
+            arg!(-c --colors <COUNT> "Number of dominant colors")
+                .value_parser(value_parser!(u16).range(1..=256))
+                .default_value("8")
+                .action(ArgAction::Set),
+        )
+        .arg(
+            arg!(--method <METHOD> "How the colors are picked")
+                .value_parser(
+                    PossibleValuesParser::new(["median-cut", "k-means"])
+                        .map(|s| s.parse::<PaletteMethod>().unwrap()),
+                )
+                .default_value("median-cut")
+                .action(ArgAction::Set),
+        )
+        .arg(
+            arg!(--report <FORMAT> "hex and json are printed to stdout unless -o or --out-dir is set, which multiple inputs require, swatch (png), gpl and ase are saved as <input>_palette.<ext>")
+                .value_parser(value_parser!(Report))
+                .default_value("hex")
+                .action(ArgAction::Set),
         )
-        .get_matches()
+}
+
+/// Adds trailing `steps` argument which collects the rest of the chain
+fn chainable(command: Command) -> Command {
+    command.arg(
+        arg!([steps] ... "Further operations to apply to the result")
+            .trailing_var_arg(true)
+            .allow_hyphen_values(true)
+            .value_parser(value_parser!(String))
+            .action(ArgAction::Append),
+    )
+}
+
+/// Unrolls chained subcommands into a list of operations in order of application
+///
+/// Returns pairs of operation name and its ArgMatches
+pub fn make_pipeline(matches: &ArgMatches) -> Vec<(String, ArgMatches)> {
+    let mut steps = Vec::new();
+    let mut current = matches
+        .subcommand()
+        .map(|(name, m)| (name.to_string(), m.clone()));
+
+    while let Some((name, sub_matches)) = current.take() {
+        let rest = sub_matches
+            .try_get_many::<String>("steps")
+            .ok()
+            .flatten()
+            .map(|values| values.cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        if !rest.is_empty() {
+            let next = Command::new(crate_name!())
+                .no_binary_name(true)
+                .subcommand_required(true)
+                .subcommands(operations())
+                .get_matches_from(rest);
+            current = next
+                .subcommand()
+                .map(|(name, m)| (name.to_string(), m.clone()));
+        }
+
+        steps.push((name, sub_matches));
+    }
+
+    steps
 }
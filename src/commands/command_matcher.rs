@@ -1,58 +1,243 @@
-use crate::ascii::{self, render_to_file};
 use clap::ArgMatches;
+use image::codecs::hdr::{HdrDecoder, HdrMetadata};
 use image::io::Reader as ImageReader;
-use image::ImageFormat;
-use std::error::Error;
-use std::io;
-use std::path::PathBuf;
+use image::{DynamicImage, ImageFormat, Rgb32FImage};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, Cursor, Read, Seek, Write};
+use std::path::{Path, PathBuf};
 use unicode_segmentation::UnicodeSegmentation;
 
-use super::{blur, curse, grayscale, monochrome_ugly, pixelate, resize, rotate, zxc, Args};
-use ascii::{from_str, render, RenderOptions};
+use super::batch::{collect_inputs, Input};
+use super::command_creator::TERMINAL;
+use super::operations::find;
+use super::output::{encode, resolve_format, save, OutputOptions};
+use super::palette::{report_palette, Report};
+use super::recipe::Recipe;
+use super::{make_pipeline, Args};
+use climp::ascii::{from_str, render_image, render_image_to_file, RenderOptions};
+use climp::{AnyImage, ClimpError, Luminance};
+
+/// Path which stands for stdin as an input and for stdout as an output
+const STDIO: &str = "-";
 
 impl Args {
-    /// Matches command line arguments and calls the appropriate function
-    /// based on matches.subcommand() method
+    /// Matches command line arguments and applies the chain of operations
+    /// unrolled by `make_pipeline` to every input file
     ///
     /// Takes Args struct and ArgMatches as input
     ///
-    /// If no file extension is provided then jpg will be used
-    pub fn match_command(&mut self, matches: ArgMatches) -> Result<(), Box<dyn Error>> {
-        /* Someday I'll use this for batch processing
-           .get_many::<String>("filepath")
-           .unwrap_or_default()
-           .map(|v| v.as_str())
-           .collect::<Vec<_>>();
-        */
-
-        if let Some(path) = matches.get_one::<PathBuf>("filepath") {
-            let file_ext_name = std::path::Path::new(&path)
+    /// Each image is decoded once and saved once, no matter how many operations are chained.
+    /// Failures are reported per file and summarized once the whole batch is processed.
+    ///
+    /// `run` subcommand takes all of the above from the recipe file instead
+    pub fn match_command(&mut self, matches: ArgMatches) -> Result<(), ClimpError> {
+        let mut patterns = matches
+            .get_many::<PathBuf>("filepath")
+            .unwrap_or_default()
+            .cloned()
+            .collect::<Vec<_>>();
+        if let Some(sub_matches) = matches.subcommand_matches("palette") {
+            patterns.extend(
+                sub_matches
+                    .get_many::<PathBuf>("filepath")
+                    .unwrap_or_default()
+                    .cloned(),
+            );
+        }
+        let mut output = matches.get_one::<PathBuf>("output").cloned();
+        self.set_recursive(matches.get_flag("recursive"));
+        self.set_out_dir(matches.get_one::<PathBuf>("out-dir").cloned());
+
+        let steps = if let Some(("run", sub_matches)) = matches.subcommand() {
+            let recipe = Recipe::load(sub_matches.get_one::<PathBuf>("recipe").unwrap())?;
+            if patterns.is_empty() {
+                patterns = recipe.inputs;
+            }
+            output = output.or(recipe.output);
+            self.set_recursive(self.is_recursive() || recipe.recursive);
+            if self.get_out_dir().is_none() {
+                self.set_out_dir(recipe.out_dir);
+            }
+            self.set_output_options(recipe.output_options);
+            recipe.steps
+        } else {
+            make_pipeline(&matches)
+        };
+        if steps.is_empty() {
+            return Err(ClimpError::InvalidArgument(String::from(
+                "no command given, use '--help' for the list of commands",
+            )));
+        }
+
+        // Options given on the command line override the recipe ones
+        self.set_output_options(
+            OutputOptions::from_matches(&matches).or(*self.get_output_options()),
+        );
+        let report = reported_palette(&steps);
+        if patterns.iter().any(|p| p == Path::new(STDIO))
+            && output.is_none()
+            && !report.is_some_and(Report::is_printed)
+        {
+            return Err(ClimpError::InvalidArgument(String::from(
+                "--output is required when reading from stdin",
+            )));
+        }
+        let renders_image = !steps
+            .last()
+            .is_some_and(|(name, _)| TERMINAL.contains(&name.as_str()));
+        if output.as_deref() == Some(Path::new(STDIO))
+            && self.get_output_options().format.is_none()
+            && renders_image
+        {
+            return Err(ClimpError::InvalidArgument(String::from(
+                "--format is required when writing to stdout",
+            )));
+        }
+
+        self.process_batch(&patterns, output.as_ref(), &steps)
+    }
+
+    /// Applies the steps to every file found by the patterns
+    ///
+    /// A single failed file is returned as is, so its kind defines the exit code
+    fn process_batch(
+        &mut self,
+        patterns: &[PathBuf],
+        output: Option<&PathBuf>,
+        steps: &[(String, ArgMatches)],
+    ) -> Result<(), ClimpError> {
+        let (inputs, mut failures) = collect_inputs(patterns, self.is_recursive());
+        let total = inputs.len() + failures.len();
+        // Single file errors are printed by the caller only
+        if total > 1 {
+            for e in &failures {
+                eprintln!("{e}");
+            }
+        }
+        if output.is_some() && total > 1 {
+            return Err(ClimpError::InvalidArgument(String::from(
+                "--output can't be used with multiple inputs, use --out-dir instead",
+            )));
+        }
+        let report = reported_palette(steps);
+        if total > 1 && report.is_some_and(Report::is_printed) && self.get_out_dir().is_none() {
+            return Err(ClimpError::InvalidArgument(String::from(
+                "palette reports of multiple inputs can't be told apart on stdout, use --out-dir to save one per input",
+            )));
+        }
+
+        // Every output is named before anything is written, so that inputs
+        // sharing a name fail instead of overwriting each other's results
+        let mut named = Vec::with_capacity(inputs.len());
+        for input in &inputs {
+            self.select_input(input);
+            let result = match report {
+                Some(report) => self.format_report_name(output, &input.relative, report),
+                None => self.format_output_name(output, &input.relative),
+            };
+            match result {
+                Ok(()) => named.push((input, self.get_output_name().clone())),
+                Err(e) => {
+                    if total > 1 {
+                        eprintln!("{e}");
+                    }
+                    failures.push(e);
+                }
+            }
+        }
+        let mut claimed = HashMap::<&Path, Vec<&Path>>::new();
+        for (input, name) in &named {
+            if name != Path::new(STDIO) {
+                claimed.entry(name).or_default().push(&input.path);
+            }
+        }
+
+        for (input, name) in &named {
+            let result = match claimed.get(name.as_path()) {
+                Some(paths) if paths.len() > 1 => Err(ClimpError::InvalidArgument(format!(
+                    "{name:?} would be written by {} inputs: {}, use --out-dir with their directory to keep them apart",
+                    paths.len(),
+                    paths
+                        .iter()
+                        .map(|p| format!("{p:?}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))),
+                _ => {
+                    self.select_input(input);
+                    self.set_output_name(name.clone());
+                    self.process_file(steps)
+                }
+            };
+            if let Err(e) = result {
+                if total > 1 {
+                    eprintln!("{e}");
+                }
+                failures.push(e);
+            }
+        }
+
+        if total > 1 {
+            let mut summary = format!(
+                "Processed {} files: {} succeeded, {} failed",
+                total,
+                total - failures.len(),
+                failures.len()
+            );
+            for e in &failures {
+                summary += &format!("\n  {e}");
+            }
+            // stdout taken by the results is left to them
+            if prints_results(steps) {
+                eprintln!("{summary}");
+            } else {
+                println!("{summary}");
+            }
+        }
+
+        match failures.len() {
+            0 => Ok(()),
+            1 if total == 1 => Err(failures.remove(0)),
+            failed => Err(ClimpError::Batch { failed, total }),
+        }
+    }
+
+    /// Makes the input the current file
+    fn select_input(&mut self, input: &Input) {
+        self.set_filepath(input.path.clone());
+        self.set_file_ext(Some(
+            input
+                .path
                 .extension()
                 .and_then(std::ffi::OsStr::to_str)
                 .unwrap_or("jpg")
-                .to_string();
-            self.set_filepath(PathBuf::from(path));
-            self.set_file_ext(Some(file_ext_name));
-        }
+                .to_string(),
+        ));
+    }
 
-        if let Some(name) = matches.get_one::<PathBuf>("output") {
+    /// Formats output filename
+    ///
+    /// `-o` is used as is, otherwise the input name gets `_edited` suffix
+    /// unless `--out-dir` is set, which keeps the input name as `relative` to the directory.
+    ///
+    /// Generated names keep the input extension, or get the extension of the format if it's set
+    fn format_output_name(
+        &mut self,
+        output: Option<&PathBuf>,
+        relative: &Path,
+    ) -> Result<(), ClimpError> {
+        if let Some(name) = output {
             self.set_output_name(PathBuf::from(name));
-            self.set_output_ext(Some(String::from(
-                match self
-                    .get_output_name()
-                    .extension()
-                    .and_then(std::ffi::OsStr::to_str)
-                {
-                    Some(ext) => {
-                        if ImageFormat::from_extension(ext).is_some() {
-                            ext
-                        } else {
-                            "jpg"
-                        }
-                    }
-                    None => "jpg",
-                },
-            )));
+            return Ok(());
+        }
+
+        if let Some(dir) = self.get_out_dir() {
+            let name = dir.join(relative);
+            if let Some(parent) = name.parent() {
+                fs::create_dir_all(parent).map_err(|e| ClimpError::Io(parent.into(), e))?;
+            }
+            self.set_output_name(name);
         } else {
             self.set_output_name(PathBuf::from(
                 self.get_filepath()
@@ -63,210 +248,224 @@ impl Args {
                     .to_string()
                     + "_edited"
                     + "."
-                    + self
-                        .get_file_ext()
-                        .unwrap_or(&String::from("jpg")),
+                    + self.get_file_ext().unwrap_or(&String::from("jpg")),
             ));
-            self.set_output_ext(Some(String::from("jpg")));
         }
 
-        if let Some(subcommand_matches) = matches.subcommand_matches("ascii") {
-            if subcommand_matches.get_flag("colored") {
-                self.set_colored(true);
-            }
-            if subcommand_matches.get_flag("verbose_only") {
-                self.set_verbose_only(true);
-            }
-            if subcommand_matches.get_flag("invert") {
-                self.set_invert(true);
+        if let Some(format) = self.get_output_options().format {
+            let ext = format.extensions_str()[0];
+            self.set_output_name(self.get_output_name().with_extension(ext));
+        }
+        Ok(())
+    }
+
+    /// Formats the name of the `palette` report
+    ///
+    /// `-o` is used as is, `--out-dir` keeps the input name with the extension of the report.
+    /// Otherwise hex and json are printed to stdout and palette files are named
+    /// after the input with `_palette` suffix
+    fn format_report_name(
+        &mut self,
+        output: Option<&PathBuf>,
+        relative: &Path,
+        report: Report,
+    ) -> Result<(), ClimpError> {
+        if let Some(name) = output {
+            self.set_output_name(PathBuf::from(name));
+        } else if let Some(dir) = self.get_out_dir() {
+            let name = dir.join(relative).with_extension(report.extension());
+            if let Some(parent) = name.parent() {
+                fs::create_dir_all(parent).map_err(|e| ClimpError::Io(parent.into(), e))?;
             }
+            self.set_output_name(name);
+        } else if report.is_printed() {
+            self.set_output_name(PathBuf::from(STDIO));
+        } else {
+            self.set_output_name(PathBuf::from(format!(
+                "{}_palette.{}",
+                self.input_stem(),
+                report.extension()
+            )));
         }
+        Ok(())
+    }
 
-        if let Some(name) = matches
-            .subcommand_matches("ascii")
-            .and_then(|m| m.get_one::<String>("charset"))
-        {
-            self.set_charset(name.to_string())
+    /// Name of the current file without its extension, `stdin` when reading it
+    fn input_stem(&self) -> &str {
+        match self.get_filepath() {
+            path if path == Path::new(STDIO) => "stdin",
+            path => path.file_stem().and_then(|s| s.to_str()).unwrap_or("image"),
         }
-        if let Some(name) = matches
-            .subcommand_matches("ascii")
-            .and_then(|m| m.get_one::<u32>("width"))
-        {
-            self.set_width(Some(*name))
-        };
-        if let Some(name) = matches
-            .subcommand_matches("ascii")
-            .and_then(|m| m.get_one::<u32>("height"))
-        {
-            self.set_height(Some(*name))
+    }
+
+    /// Decodes the current file, applies every step to it and saves the result
+    ///
+    /// Output format is resolved first, so that unsupported ones fail before decoding
+    fn process_file(&mut self, steps: &[(String, ArgMatches)]) -> Result<(), ClimpError> {
+        let renders_image = !steps
+            .last()
+            .is_some_and(|(name, _)| TERMINAL.contains(&name.as_str()));
+        let format = if !renders_image {
+            None
+        } else {
+            Some(resolve_format(
+                self.get_output_name(),
+                self.get_output_options(),
+            )?)
         };
 
-        match matches.subcommand() {
-            Some(("blur", sub_matches)) => {
-                if let Some(r) = sub_matches.get_one::<u32>("blur_radius") {
-                    self.set_radius(Some(*r));
-                    let img_result = blur(
-                        &ImageReader::open(self.get_filepath().clone())?
-                            .decode()?
-                            .into_rgba8(),
-                        *r,
-                    );
-                    let _ = img_result.save_with_format(
-                        self.get_output_name(),
-                        ImageFormat::from_extension(self.get_output_ext().as_deref().unwrap())
-                            .unwrap_or(ImageFormat::Jpeg),
-                    );
-                    println!("Blurred image saved as {:?}", self.get_output_name());
-                }
-            }
-            Some(("pixelate", sub_matches)) => {
-                let img = ImageReader::open(self.get_filepath().clone())?.decode()?;
-                if let Some(s) = sub_matches.get_one::<u32>("pixel_size") {
-                    self.set_pixel(Some(*s));
-                    let img_result = pixelate(&img, (*s, *s));
-                    let _ = img_result.save_with_format(
-                        self.get_output_name(),
-                        ImageFormat::from_extension(self.get_output_ext().as_deref().unwrap())
-                            .unwrap_or(ImageFormat::Jpeg),
-                    );
-                    println!("Pixelated image saved as {:?}", self.get_output_name());
-                }
-            }
-            Some(("scale", sub_matches)) => {
-                let img = ImageReader::open(self.get_filepath().clone())?.decode()?;
-                if let Some(s) = sub_matches.get_one::<u32>("scale") {
-                    self.set_resize(Some(*s));
-                    let img_result = resize(&img.into_rgba8(), (*s, *s));
-                    let _ = img_result.save_with_format(
-                        self.get_output_name(),
-                        ImageFormat::from_extension(self.get_output_ext().as_deref().unwrap())
-                            .unwrap_or(ImageFormat::Jpeg),
-                    );
-                    println!("Scaled image saved as {:?}", self.get_output_name());
-                }
-            }
-            Some(("rotate", _sub_matches)) => {
-                let img_result = rotate(
-                    &ImageReader::open(self.get_filepath().clone())?
-                        .decode()?
-                        .into_rgba8(),
-                );
-                let _ = img_result.save_with_format(
-                    self.get_output_name(),
-                    ImageFormat::from_extension(self.get_output_ext().as_deref().unwrap())
-                        .unwrap_or(ImageFormat::Jpeg),
-                );
-                println!("Rotated image saved as {:?}", self.get_output_name());
-            }
-            Some(("mirror", _sub_matches)) => {
-                let img = ImageReader::open(self.get_filepath().clone())?.decode()?;
-                let img_result = img.fliph();
-                let _ = img_result.save_with_format(
-                    self.get_output_name(),
-                    ImageFormat::from_extension(self.get_output_ext().as_deref().unwrap())
-                        .unwrap_or(ImageFormat::Jpeg),
-                );
-                println!("Mirrored image saved as {:?}", self.get_output_name());
-            }
-            Some(("flip_vertical", _sub_matches)) => {
-                let img = ImageReader::open(self.get_filepath().clone())?.decode()?;
-                let img_result = img.flipv();
-                let _ = img_result.save_with_format(
-                    self.get_output_name(),
-                    ImageFormat::from_extension(self.get_output_ext().as_deref().unwrap())
-                        .unwrap_or(ImageFormat::Jpeg),
-                );
-                println!("Flipped image saved as {:?}", self.get_output_name());
-            }
-            Some(("monochrome_ugly", sub_matches)) => {
-                if let Some(t) = sub_matches.get_one::<f32>("threshold") {
-                    self.set_threshold(Some(*t));
-                    let img_result = monochrome_ugly(
-                        &ImageReader::open(self.get_filepath().clone())?
-                            .decode()?
-                            .into_rgba8(),
-                        *t,
-                    );
-                    let _ = img_result.save_with_format(
-                        self.get_output_name(),
-                        ImageFormat::from_extension(self.get_output_ext().as_deref().unwrap())
-                            .unwrap_or(ImageFormat::Jpeg),
-                    );
-                    println!("Monochrome image saved as {:?}", self.get_output_name());
+        let mut img = self.decode_input()?;
+        let mut applied = Vec::new();
+
+        for (name, sub_matches) in steps {
+            match find(name) {
+                Some(op) => {
+                    img = op.apply(&img, sub_matches)?;
+                    applied.push(op.label());
                 }
-            }
-            Some(("grayscale", _sub_matches)) => {
-                let img_result = grayscale(
-                    &ImageReader::open(self.get_filepath().clone())?
-                        .decode()?
-                        .into_rgba8(),
-                );
-                let _ = img_result.save_with_format(
-                    self.get_output_name(),
-                    ImageFormat::from_extension(self.get_output_ext().as_deref().unwrap())
-                        .unwrap_or(ImageFormat::Jpeg),
-                );
-                println!("Grayscale image saved as {:?}", self.get_output_name());
-            }
-            Some(("ascii", sub_matches)) => {
-                let clusters = UnicodeSegmentation::graphemes(self.get_charset().as_str(), true)
-                    .collect::<Vec<_>>();
-                let charset = from_str(self.get_charset().as_str()).unwrap_or(clusters.as_slice());
-
-                let path = self.get_filepath().clone();
-
-                if sub_matches.get_flag("verbose_only") {
-                    render(
-                        path.to_str().unwrap(),
-                        &mut io::stdout(),
-                        &RenderOptions {
-                            width: self.get_width().or(Some(80)),
-                            height: self.get_height(),
-                            colored: self.is_colored(),
-                            invert: self.is_invert(),
-                            charset,
-                        },
-                    )?;
-                } else {
-                    render_to_file(
-                        path.to_str().unwrap(),
-                        self.get_output_name(),
-                        &RenderOptions {
-                            width: self.get_width().or(Some(80)),
-                            height: self.get_height(),
-                            colored: self.is_colored(),
-                            invert: self.is_invert(),
-                            charset,
-                        },
-                    )?;
+                // ascii and palette have no `steps` argument so they always end the chain
+                None if name == "palette" => {
+                    let name = self.input_stem();
+                    return report_palette(&img, sub_matches, self.get_output_name(), name);
                 }
+                None => return self.render_ascii(&img, sub_matches),
             }
-            Some(("curse", _sub_matches)) => {
-                let img_result = curse(
-                    &ImageReader::open(self.get_filepath().clone())?
-                        .decode()?
-                        .into_rgba8(),
-                );
-                let _ = img_result.save_with_format(
-                    self.get_output_name(),
-                    ImageFormat::from_extension(self.get_output_ext().as_deref().unwrap())
-                        .unwrap_or(ImageFormat::Jpeg),
-                );
-                println!("Cursed image saved as {:?}", self.get_output_name());
-            }
-            Some(("zxc", _sub_matches)) => {
-                let img_result = zxc(&ImageReader::open(self.get_filepath().clone())?
-                    .decode()?
-                    .into_rgba8());
-                let _ = img_result.save_with_format(
-                    self.get_output_name(),
-                    ImageFormat::from_extension(self.get_output_ext().as_deref().unwrap())
-                        .unwrap_or(ImageFormat::Jpeg),
-                );
-                println!("ZXCursed image saved as {:?}", self.get_output_name());
-            }
-            _ => println!("Unidentified subcommand. \n Use '--help' for more information"),
         }
+
+        // format is resolved for every chain which ends with an image
+        self.encode_output(&img, format.unwrap())?;
+        if self.get_output_name() == Path::new(STDIO) {
+            // stdout is taken by the image itself
+            eprintln!("{} image written to stdout", applied.join(", "));
+        } else {
+            println!(
+                "{} image saved as {:?}",
+                applied.join(", "),
+                self.get_output_name()
+            );
+        }
+
         Ok(())
     }
+
+    /// Decodes the current file, format of stdin is guessed from its content
+    fn decode_input(&self) -> Result<AnyImage, ClimpError> {
+        let path = self.get_filepath();
+        if path != Path::new(STDIO) {
+            let reader = ImageReader::open(path).map_err(|e| ClimpError::Io(path.clone(), e))?;
+            return decode(reader, path);
+        }
+
+        let mut bytes = Vec::new();
+        io::stdin()
+            .read_to_end(&mut bytes)
+            .map_err(|e| ClimpError::Io(path.clone(), e))?;
+        let reader = ImageReader::new(Cursor::new(bytes))
+            .with_guessed_format()
+            .map_err(|e| ClimpError::Io(path.clone(), e))?;
+        decode(reader, Path::new("stdin"))
+    }
+
+    /// Encodes the result into the output file or into stdout
+    fn encode_output(&self, img: &AnyImage, format: ImageFormat) -> Result<(), ClimpError> {
+        let name = self.get_output_name();
+        if name != Path::new(STDIO) {
+            return save(img, name, format, self.get_output_options());
+        }
+
+        let mut stdout = io::stdout().lock();
+        encode(
+            img,
+            &mut stdout,
+            format,
+            self.get_output_options(),
+            Path::new("stdout"),
+        )?;
+        stdout
+            .flush()
+            .map_err(|e| ClimpError::Io(PathBuf::from("stdout"), e))
+    }
+
+    /// Renders the processed image as an ASCII art
+    ///
+    /// Prints it to stdout if `verbose_only` is set or output is `-`, otherwise writes it to the output file.
+    /// `--equalize` runs the image through `equalize` or `clahe` operation first
+    fn render_ascii(&mut self, img: &AnyImage, sub_matches: &ArgMatches) -> Result<(), ClimpError> {
+        let img = match sub_matches
+            .get_one::<String>("equalize")
+            .map(String::as_str)
+        {
+            Some("global") => find("equalize").unwrap().apply(img, sub_matches)?,
+            Some(_) => find("clahe").unwrap().apply(img, sub_matches)?,
+            None => img.clone(),
+        };
+        let img = DynamicImage::from(img);
+        self.set_colored(sub_matches.get_flag("colored"));
+        self.set_verbose_only(sub_matches.get_flag("verbose_only"));
+        self.set_invert(sub_matches.get_flag("invert"));
+        if let Some(name) = sub_matches.get_one::<String>("charset") {
+            self.set_charset(name.to_string())
+        }
+        self.set_width(sub_matches.get_one::<u32>("width").copied());
+        self.set_height(sub_matches.get_one::<u32>("height").copied());
+
+        let clusters =
+            UnicodeSegmentation::graphemes(self.get_charset().as_str(), true).collect::<Vec<_>>();
+        let charset = from_str(self.get_charset().as_str()).unwrap_or(clusters.as_slice());
+
+        let options = RenderOptions {
+            width: self.get_width().or(Some(80)),
+            height: self.get_height(),
+            colored: self.is_colored(),
+            invert: self.is_invert(),
+            charset,
+            luminance: sub_matches
+                .get_one::<Luminance>("luminance")
+                .copied()
+                .unwrap_or_default(),
+        };
+
+        if self.get_verbose_only() || self.get_output_name() == Path::new(STDIO) {
+            render_image(&img, &mut io::stdout(), &options)
+                .map_err(|e| ClimpError::encode("stdout", e))?;
+        } else {
+            render_image_to_file(&img, self.get_output_name(), &options)
+                .map_err(|e| ClimpError::encode(self.get_output_name(), e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Report of the `palette` step ending the chain, if there's one
+fn reported_palette(steps: &[(String, ArgMatches)]) -> Option<Report> {
+    match steps.last() {
+        Some((name, sub_matches)) if name == "palette" => {
+            sub_matches.get_one::<Report>("report").copied()
+        }
+        _ => None,
+    }
+}
+
+/// Whether the chain prints its result of every input to stdout, as `ascii -v` does
+fn prints_results(steps: &[(String, ArgMatches)]) -> bool {
+    steps
+        .last()
+        .is_some_and(|(name, sub_matches)| name == "ascii" && sub_matches.get_flag("verbose_only"))
+}
+
+/// Decodes the image in the precision of its format
+///
+/// image crate decodes Hdr as tone mapped 8-bit, so its float pixels are read directly
+fn decode<R: BufRead + Seek>(reader: ImageReader<R>, name: &Path) -> Result<AnyImage, ClimpError> {
+    let error = |e| ClimpError::decode(name, e);
+    if reader.format() != Some(ImageFormat::Hdr) {
+        return reader.decode().map(AnyImage::from).map_err(error);
+    }
+
+    let decoder = HdrDecoder::new(reader.into_inner()).map_err(error)?;
+    let HdrMetadata { width, height, .. } = decoder.metadata();
+    let pixels = decoder.read_image_hdr().map_err(error)?;
+    let rgb = Rgb32FImage::from_raw(width, height, pixels.iter().flat_map(|p| p.0).collect())
+        .expect("Hdr decoder returns width * height pixels");
+    Ok(AnyImage::from(DynamicImage::ImageRgb32F(rgb)))
 }
@@ -1,10 +1,12 @@
 mod args;
+mod batch;
 mod command_creator;
 mod command_matcher;
-mod methods;
+mod operation;
+mod operations;
+mod output;
+mod palette;
+mod recipe;
 
 pub use crate::commands::args::Args;
-pub use crate::commands::command_creator::make_commands;
-pub use crate::commands::methods::{
-    blur, curse, grayscale, monochrome_ugly, pixelate, resize, rotate, zxc,
-};
+pub use crate::commands::command_creator::{make_commands, make_pipeline};
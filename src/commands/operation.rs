@@ -0,0 +1,203 @@
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{arg, value_parser, Arg, ArgAction, ArgMatches, Command};
+use climp::{flatten, map_image, parse_color, AnyImage, ClimpError, Dither, Gravity, Luminance};
+use image::Rgba;
+
+/// Image operation available as a subcommand
+///
+/// Operations are listed in the `operations` registry, which generates
+/// both the subcommands and the dispatch of the parsed chain
+pub trait Operation: Sync {
+    /// Name of the subcommand
+    fn name(&self) -> &'static str;
+
+    /// Past tense used in the "... image saved as" message
+    fn label(&self) -> &'static str;
+
+    /// Subcommand with the operation arguments
+    fn command(&self) -> Command;
+
+    /// Applies the operation to the image with arguments parsed by `command`
+    fn apply(&self, img: &AnyImage, matches: &ArgMatches) -> Result<AnyImage, ClimpError>;
+}
+
+/// Gets value of the argument parsed by clap
+///
+/// Returns an error instead of panicking when the argument is missing
+pub fn arg<T: Clone + Send + Sync + 'static>(
+    matches: &ArgMatches,
+    id: &str,
+) -> Result<T, ClimpError> {
+    matches
+        .try_get_one::<T>(id)
+        .ok()
+        .flatten()
+        .cloned()
+        .ok_or_else(|| ClimpError::InvalidArgument(format!("missing value for {id}")))
+}
+
+/// Creates `--background` argument of the operations which keep alpha channel
+pub fn background_arg() -> Arg {
+    arg!(--background <COLOR> "Flatten transparent areas onto the color first, e.g. white or #ff8800")
+        .value_parser(parse_color)
+        .action(ArgAction::Set)
+}
+
+/// Flattens the image onto `--background` if it's given, otherwise returns it as is
+pub fn with_background(img: &AnyImage, matches: &ArgMatches) -> AnyImage {
+    match matches.get_one::<Rgba<u8>>("background") {
+        Some(background) => map_image!(img, |buf| flatten(buf, *background)),
+        None => img.clone(),
+    }
+}
+
+/// Creates `--tile_size` and `--clip_limit` arguments of CLAHE, shared by `clahe` and `ascii`
+pub fn clahe_args() -> [Arg; 2] {
+    [
+        arg!(--tile_size <PIXELS> "Side of the square tiles equalized on their own")
+            .default_value("64")
+            .value_parser(value_parser!(u32).range(1..))
+            .action(ArgAction::Set),
+        arg!(--clip_limit <VALUE> "Max height of histogram bins relative to the average, 1 keeps the contrast")
+            .default_value("2")
+            .value_parser(parse_clip_limit)
+            .action(ArgAction::Set),
+    ]
+}
+
+fn parse_clip_limit(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(limit) if limit >= 1.0 && limit.is_finite() => Ok(limit),
+        _ => Err(format!("`{value}` is not a number from 1 up")),
+    }
+}
+
+/// Creates `--luminance` argument choosing how colors turn into brightness, rec709 by default
+pub fn luminance_arg() -> Arg {
+    arg!(--luminance <MODE> "How colors turn into brightness")
+        .value_parser(
+            PossibleValuesParser::new([
+                "average",
+                "rec601",
+                "rec709",
+                "lightness",
+                "red",
+                "green",
+                "blue",
+                "lab",
+            ])
+            .map(|s| s.parse::<Luminance>().unwrap()),
+        )
+        .default_value("rec709")
+        .action(ArgAction::Set)
+}
+
+/// Parses names of the dithering methods, shared by `dither` and `quantize`
+pub fn dither_parser() -> impl TypedValueParser<Value = Dither> {
+    PossibleValuesParser::new([
+        "floyd-steinberg",
+        "atkinson",
+        "jarvis-judice-ninke",
+        "sierra",
+        "bayer2",
+        "bayer4",
+        "bayer8",
+    ])
+    .map(|s| s.parse::<Dither>().unwrap())
+}
+
+/// Creates `--gravity` argument anchoring a region inside of the image, center by default
+pub fn gravity_arg(help: &'static str) -> Arg {
+    arg!(--gravity <GRAVITY>)
+        .help(help)
+        .value_parser(
+            PossibleValuesParser::new([
+                "center",
+                "north",
+                "south",
+                "east",
+                "west",
+                "northeast",
+                "northwest",
+                "southeast",
+                "southwest",
+            ])
+            .map(|s| s.parse::<Gravity>().unwrap()),
+        )
+        .default_value("center")
+        .action(ArgAction::Set)
+}
+
+/// Most bytes the result of an operation may take, larger canvases are rejected before allocating them
+const MAX_CANVAS_BYTES: u64 = 4 << 30;
+
+/// Checks that `action` turning the image into a `(width, height)` canvas stays within `MAX_CANVAS_BYTES`
+///
+/// Sizes are taken as `u64`, so callers can add and multiply sides without overflowing
+pub fn check_canvas(
+    img: &AnyImage,
+    (width, height): (u64, u64),
+    action: &str,
+) -> Result<(), ClimpError> {
+    // 4 channels of bit_depth / 8 bytes each
+    let bytes = width
+        .saturating_mul(height)
+        .saturating_mul(img.bit_depth() as u64 / 2);
+    if width <= u32::MAX as u64 && height <= u32::MAX as u64 && bytes <= MAX_CANVAS_BYTES {
+        return Ok(());
+    }
+    let (w, h) = img.dimensions();
+    Err(ClimpError::InvalidArgument(format!(
+        "{action} makes {w}x{h} image too large, {width}x{height} pixels take more than {} GiB",
+        MAX_CANVAS_BYTES >> 30
+    )))
+}
+
+/// Checks that the image extended by `(left, top, right, bottom)` paddings passes `check_canvas`
+pub fn checked_padding(
+    img: &AnyImage,
+    padding: (u32, u32, u32, u32),
+) -> Result<(u32, u32, u32, u32), ClimpError> {
+    let (width, height) = img.dimensions();
+    let (left, top, right, bottom) = padding;
+    let canvas = (
+        left as u64 + width as u64 + right as u64,
+        top as u64 + height as u64 + bottom as u64,
+    );
+    check_canvas(
+        img,
+        canvas,
+        &format!("padding by {left}, {top}, {right}, {bottom} pixels"),
+    )?;
+    Ok(padding)
+}
+
+/// Parses a number or a percentage like `50%`, which is divided by 100
+///
+/// Any finite number is accepted, its range is checked by the caller
+pub fn parse_factor(value: &str) -> Result<f64, String> {
+    let (number, divisor) = match value.strip_suffix('%') {
+        Some(percent) => (percent, 100.0),
+        None => (value, 1.0),
+    };
+    match number.trim().parse::<f64>() {
+        Ok(factor) if factor.is_finite() => Ok(factor / divisor),
+        _ => Err(format!("`{value}` is not a number or percentage")),
+    }
+}
+
+/// Parses `width:height` or a single ratio number
+pub fn parse_aspect(value: &str) -> Result<f64, String> {
+    let ratio = match value.split_once(':') {
+        Some((w, h)) => w
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .zip(h.trim().parse::<f64>().ok())
+            .map(|(w, h)| w / h),
+        None => value.trim().parse::<f64>().ok(),
+    };
+    ratio
+        .filter(|r| r.is_finite() && *r > 0.0)
+        .ok_or_else(|| format!("`{value}` is not an aspect ratio like 16:9 or 1.5"))
+}
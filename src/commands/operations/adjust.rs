@@ -0,0 +1,85 @@
+use clap::{arg, value_parser, ArgAction, ArgGroup, ArgMatches, Command};
+use climp::{adjust, map_image, Adjustments, AnyImage, ClimpError};
+
+use crate::commands::operation::Operation;
+
+pub struct Adjust;
+
+impl Operation for Adjust {
+    fn name(&self) -> &'static str {
+        "adjust"
+    }
+
+    fn label(&self) -> &'static str {
+        "Adjusted"
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about("Adjust brightness, contrast, gamma and exposure")
+            .arg_required_else_help(true)
+            .arg(
+                arg!(--brightness <PERCENT> "Brightness offset from -100 to 100")
+                    .value_parser(parse_percent)
+                    .allow_negative_numbers(true)
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--contrast <PERCENT> "Contrast change from -100 (flat gray) up, 100 doubles it")
+                    .value_parser(parse_contrast)
+                    .allow_negative_numbers(true)
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--gamma <VALUE> "Gamma correction, above 1 brightens midtones")
+                    .value_parser(parse_gamma)
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--exposure <STOPS> "Exposure change in stops, +1 doubles the light")
+                    .value_parser(value_parser!(f32))
+                    .allow_negative_numbers(true)
+                    .action(ArgAction::Set),
+            )
+            .group(
+                ArgGroup::new("adjustments")
+                    .args(["brightness", "contrast", "gamma", "exposure"])
+                    .multiple(true)
+                    .required(true),
+            )
+    }
+
+    fn apply(&self, img: &AnyImage, matches: &ArgMatches) -> Result<AnyImage, ClimpError> {
+        let defaults = Adjustments::default();
+        let value = |id: &str, default: f32| matches.get_one::<f32>(id).copied().unwrap_or(default);
+        let adjustments = Adjustments {
+            brightness: value("brightness", defaults.brightness),
+            contrast: value("contrast", defaults.contrast),
+            gamma: value("gamma", defaults.gamma),
+            exposure: value("exposure", defaults.exposure),
+        };
+
+        Ok(map_image!(img, |buf| adjust(buf, &adjustments)))
+    }
+}
+
+fn parse_percent(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(percent) if (-100.0..=100.0).contains(&percent) => Ok(percent),
+        _ => Err(format!("`{value}` is not a number from -100 to 100")),
+    }
+}
+
+fn parse_contrast(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(percent) if percent >= -100.0 && percent.is_finite() => Ok(percent),
+        _ => Err(format!("`{value}` is not a number from -100 up")),
+    }
+}
+
+fn parse_gamma(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(gamma) if gamma > 0.0 && gamma.is_finite() => Ok(gamma),
+        _ => Err(format!("`{value}` is not a positive number")),
+    }
+}
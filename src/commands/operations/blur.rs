@@ -0,0 +1,51 @@
+use clap::{arg, value_parser, ArgAction, ArgMatches, Command};
+use climp::{blur, map_image, AnyImage, ClimpError};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::time::Duration;
+
+use crate::commands::operation::{arg, Operation};
+
+pub struct Blur;
+
+impl Operation for Blur {
+    fn name(&self) -> &'static str {
+        "blur"
+    }
+
+    fn label(&self) -> &'static str {
+        "Blurred"
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about("Blur the image with a given radius")
+            .arg_required_else_help(true)
+            .arg(
+                arg!(-r --blur_radius <VALUE>)
+                    .required(true)
+                    .value_parser(value_parser!(u32))
+                    .action(ArgAction::Set),
+            )
+    }
+
+    fn apply(&self, img: &AnyImage, matches: &ArgMatches) -> Result<AnyImage, ClimpError> {
+        let radius = arg(matches, "blur_radius")?;
+
+        let pb = ProgressBar::new_spinner();
+        pb.enable_steady_tick(Duration::from_millis(120));
+        pb.set_style(
+            ProgressStyle::with_template("{spinner:.green} {msg}")
+                .unwrap()
+                // For more spinners check out the cli-spinners project:
+                // https://github.com/sindresorhus/cli-spinners/blob/master/spinners.json
+                .tick_strings(&[
+                    "[    ]", "[=   ]", "[==  ]", "[=== ]", "[ ===]", "[  ==]", "[   =]", "[    ]",
+                    "[   =]", "[  ==]", "[ ===]", "[====]", "[=== ]", "[==  ]", "[=   ]",
+                ]),
+        );
+        pb.set_message("\x1b[33mBlurring...\x1b[0m");
+        let blurred = map_image!(img, |buf| blur(buf, radius));
+        pb.finish_with_message("\x1b[32mDone\x1b[0m");
+        Ok(blurred)
+    }
+}
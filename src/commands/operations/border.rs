@@ -0,0 +1,40 @@
+use clap::{arg, value_parser, ArgAction, ArgMatches, Command};
+use climp::{map_image, pad, parse_color, AnyImage, ClimpError, PadFill};
+
+use crate::commands::operation::{arg, checked_padding, Operation};
+
+pub struct Border;
+
+impl Operation for Border {
+    fn name(&self) -> &'static str {
+        "border"
+    }
+
+    fn label(&self) -> &'static str {
+        "Bordered"
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about("Add a solid border around the image")
+            .arg(
+                arg!(-w --border_width <PIXELS> "Width of the border")
+                    .default_value("10")
+                    .value_parser(value_parser!(u32))
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--color <COLOR> "Color of the border, e.g. white or #ff8800")
+                    .default_value("black")
+                    .value_parser(parse_color)
+                    .action(ArgAction::Set),
+            )
+    }
+
+    fn apply(&self, img: &AnyImage, matches: &ArgMatches) -> Result<AnyImage, ClimpError> {
+        let width: u32 = arg(matches, "border_width")?;
+        let fill = PadFill::Color(arg(matches, "color")?);
+        let padding = checked_padding(img, (width, width, width, width))?;
+        Ok(map_image!(img, |buf| pad(buf, padding, fill)))
+    }
+}
@@ -0,0 +1,28 @@
+use clap::{ArgMatches, Command};
+use climp::{clahe, map_image, AnyImage, ClimpError};
+
+use crate::commands::operation::{arg, clahe_args, Operation};
+
+pub struct Clahe;
+
+impl Operation for Clahe {
+    fn name(&self) -> &'static str {
+        "clahe"
+    }
+
+    fn label(&self) -> &'static str {
+        "Equalized"
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about("Equalize luminance tile by tile with limited contrast (CLAHE)")
+            .args(clahe_args())
+    }
+
+    fn apply(&self, img: &AnyImage, matches: &ArgMatches) -> Result<AnyImage, ClimpError> {
+        let tile_size: u32 = arg(matches, "tile_size")?;
+        let clip_limit: f32 = arg(matches, "clip_limit")?;
+        Ok(map_image!(img, |buf| clahe(buf, tile_size, clip_limit)))
+    }
+}
@@ -0,0 +1,96 @@
+use clap::{arg, ArgAction, ArgGroup, ArgMatches, Command};
+use climp::{aspect_region, crop, map_image, AnyImage, ClimpError};
+
+use crate::commands::operation::{arg, gravity_arg, parse_aspect, Operation};
+
+pub struct Crop;
+
+impl Operation for Crop {
+    fn name(&self) -> &'static str {
+        "crop"
+    }
+
+    fn label(&self) -> &'static str {
+        "Cropped"
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about("Crop a region of the image")
+            .arg_required_else_help(true)
+            .arg(
+                arg!(--region <REGION> "x,y,width,height of the region in pixels or percents, e.g. 10,10,50%,50%")
+                    .value_parser(parse_region)
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--aspect <RATIO> "Crop the largest region with the aspect ratio, e.g. 16:9 or 1.5")
+                    .value_parser(parse_aspect)
+                    .action(ArgAction::Set),
+            )
+            .group(
+                ArgGroup::new("shape")
+                    .args(["region", "aspect"])
+                    .required(true),
+            )
+            .arg(gravity_arg("Where the --aspect region is anchored").conflicts_with("region"))
+    }
+
+    fn apply(&self, img: &AnyImage, matches: &ArgMatches) -> Result<AnyImage, ClimpError> {
+        let (width, height) = img.dimensions();
+        let (x, y, w, h) = match matches.get_one::<[Length; 4]>("region") {
+            Some([x, y, w, h]) => (
+                x.resolve(width),
+                y.resolve(height),
+                w.resolve(width),
+                h.resolve(height),
+            ),
+            None => aspect_region(
+                (width, height),
+                arg(matches, "aspect")?,
+                arg(matches, "gravity")?,
+            ),
+        };
+
+        Ok(map_image!(img, |buf| crop(buf, x, y, w, h)?))
+    }
+}
+
+/// Length along one of the sides of the image
+#[derive(Debug, Clone, Copy)]
+enum Length {
+    Pixels(u32),
+    Percent(f64),
+}
+
+impl Length {
+    fn resolve(self, side: u32) -> u32 {
+        match self {
+            Length::Pixels(pixels) => pixels,
+            Length::Percent(percent) => (side as f64 * percent / 100.0).round() as u32,
+        }
+    }
+}
+
+/// Parses `x,y,width,height`, each of them is a number of pixels or a percentage
+fn parse_region(value: &str) -> Result<[Length; 4], String> {
+    let lengths = value
+        .split(',')
+        .map(|part| {
+            let part = part.trim();
+            match part.strip_suffix('%') {
+                Some(percent) => percent
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|p| (0.0..=100.0).contains(p))
+                    .map(Length::Percent),
+                None => part.parse::<u32>().ok().map(Length::Pixels),
+            }
+            .ok_or_else(|| format!("`{part}` is neither pixels nor a percentage from 0% to 100%"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    lengths
+        .try_into()
+        .map_err(|_| format!("`{value}` must be x,y,width,height"))
+}
@@ -0,0 +1,26 @@
+use clap::{ArgMatches, Command};
+use climp::{curse, map_image, AnyImage, ClimpError};
+
+use crate::commands::operation::{background_arg, with_background, Operation};
+
+pub struct Curse;
+
+impl Operation for Curse {
+    fn name(&self) -> &'static str {
+        "curse"
+    }
+
+    fn label(&self) -> &'static str {
+        "Cursed"
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about("Curse the image")
+            .arg(background_arg())
+    }
+
+    fn apply(&self, img: &AnyImage, matches: &ArgMatches) -> Result<AnyImage, ClimpError> {
+        Ok(map_image!(&with_background(img, matches), |buf| curse(buf)))
+    }
+}
@@ -0,0 +1,44 @@
+use clap::{arg, ArgAction, ArgMatches, Command};
+use climp::{dither, map_image, AnyImage, ClimpError};
+
+use crate::commands::operation::{
+    arg, background_arg, dither_parser, luminance_arg, with_background, Operation,
+};
+
+pub struct Dither;
+
+impl Operation for Dither {
+    fn name(&self) -> &'static str {
+        "dither"
+    }
+
+    fn label(&self) -> &'static str {
+        "Dithered"
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about("Dither the image into black and white, e.g. for e-ink displays and thermal printers")
+            .arg(
+                arg!(-m --method <METHOD> "Error diffusion or ordered Bayer dithering with 2x2, 4x4 or 8x8 matrix")
+                    .value_parser(dither_parser())
+                    .default_value("floyd-steinberg")
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--serpentine "Scan every other row backwards to break up error diffusion patterns")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(luminance_arg())
+            .arg(background_arg())
+    }
+
+    fn apply(&self, img: &AnyImage, matches: &ArgMatches) -> Result<AnyImage, ClimpError> {
+        let method = arg(matches, "method")?;
+        let serpentine = matches.get_flag("serpentine");
+        let luminance = arg(matches, "luminance")?;
+        Ok(map_image!(&with_background(img, matches), |buf| {
+            dither(buf, method, serpentine, luminance)
+        }))
+    }
+}
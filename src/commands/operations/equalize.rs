@@ -0,0 +1,24 @@
+use clap::{ArgMatches, Command};
+use climp::{equalize, map_image, AnyImage, ClimpError};
+
+use crate::commands::operation::Operation;
+
+pub struct Equalize;
+
+impl Operation for Equalize {
+    fn name(&self) -> &'static str {
+        "equalize"
+    }
+
+    fn label(&self) -> &'static str {
+        "Equalized"
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.name()).about("Spread luminance over the whole range via its histogram")
+    }
+
+    fn apply(&self, img: &AnyImage, _matches: &ArgMatches) -> Result<AnyImage, ClimpError> {
+        Ok(map_image!(img, |buf| equalize(buf)))
+    }
+}
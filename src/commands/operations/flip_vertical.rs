@@ -0,0 +1,24 @@
+use clap::{ArgMatches, Command};
+use climp::{flip_vertical, map_image, AnyImage, ClimpError};
+
+use crate::commands::operation::Operation;
+
+pub struct FlipVertical;
+
+impl Operation for FlipVertical {
+    fn name(&self) -> &'static str {
+        "flip_vertical"
+    }
+
+    fn label(&self) -> &'static str {
+        "Flipped"
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.name()).about("Flip the image vertically")
+    }
+
+    fn apply(&self, img: &AnyImage, _matches: &ArgMatches) -> Result<AnyImage, ClimpError> {
+        Ok(map_image!(img, |buf| flip_vertical(buf)))
+    }
+}
@@ -0,0 +1,30 @@
+use clap::{ArgMatches, Command};
+use climp::{grayscale, map_image, AnyImage, ClimpError};
+
+use crate::commands::operation::{arg, background_arg, luminance_arg, with_background, Operation};
+
+pub struct Grayscale;
+
+impl Operation for Grayscale {
+    fn name(&self) -> &'static str {
+        "grayscale"
+    }
+
+    fn label(&self) -> &'static str {
+        "Grayscale"
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about("Make the image grayscale")
+            .arg(luminance_arg())
+            .arg(background_arg())
+    }
+
+    fn apply(&self, img: &AnyImage, matches: &ArgMatches) -> Result<AnyImage, ClimpError> {
+        let luminance = arg(matches, "luminance")?;
+        Ok(map_image!(&with_background(img, matches), |buf| grayscale(
+            buf, luminance
+        )))
+    }
+}
@@ -0,0 +1,100 @@
+use clap::{arg, value_parser, ArgAction, ArgGroup, ArgMatches, Command};
+use climp::{hsl, map_image, AnyImage, ClimpError, HslAdjustments};
+
+use crate::commands::operation::{arg, parse_factor, Operation};
+
+pub struct Hsl;
+
+impl Operation for Hsl {
+    fn name(&self) -> &'static str {
+        "hsl"
+    }
+
+    fn label(&self) -> &'static str {
+        "Adjusted"
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about("Rotate hue and scale saturation and lightness, optionally of a range of hues")
+            .arg_required_else_help(true)
+            .arg(
+                arg!(--hue <DEGREES> "Hue rotation in degrees")
+                    .value_parser(value_parser!(f32))
+                    .allow_negative_numbers(true)
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--saturation <FACTOR> "Saturation factor like 0.5 or 50%, 0 makes the colors gray")
+                    .value_parser(parse_non_negative)
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--lightness <FACTOR> "Lightness factor like 1.2 or 120%")
+                    .value_parser(parse_non_negative)
+                    .action(ArgAction::Set),
+            )
+            .group(
+                ArgGroup::new("changes")
+                    .args(["hue", "saturation", "lightness"])
+                    .multiple(true)
+                    .required(true),
+            )
+            .arg(
+                arg!(--hue_range <RANGE> "Only change hues from,to in degrees, e.g. 90,150, or red, yellow, green, cyan, blue or magenta")
+                    .value_parser(parse_hue_range)
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--feather <DEGREES> "Degrees beyond --hue_range over which the change fades out")
+                    .default_value("15")
+                    .value_parser(value_parser!(f32))
+                    .action(ArgAction::Set),
+            )
+    }
+
+    fn apply(&self, img: &AnyImage, matches: &ArgMatches) -> Result<AnyImage, ClimpError> {
+        let defaults = HslAdjustments::default();
+        let value = |id: &str, default: f32| matches.get_one::<f32>(id).copied().unwrap_or(default);
+        let adjustments = HslAdjustments {
+            hue: value("hue", defaults.hue),
+            saturation: value("saturation", defaults.saturation),
+            lightness: value("lightness", defaults.lightness),
+            hue_range: matches.get_one::<(f32, f32)>("hue_range").copied(),
+            feather: arg(matches, "feather")?,
+        };
+
+        Ok(map_image!(img, |buf| hsl(buf, &adjustments)))
+    }
+}
+
+/// Parses a non-negative factor or percentage
+fn parse_non_negative(value: &str) -> Result<f32, String> {
+    parse_factor(value)
+        .ok()
+        .filter(|&factor| factor >= 0.0)
+        .map(|factor| factor as f32)
+        .ok_or_else(|| format!("`{value}` is not a non-negative number or percentage"))
+}
+
+/// Parses `from,to` hues in degrees or a name of the 60 degrees wide range around a color
+fn parse_hue_range(value: &str) -> Result<(f32, f32), String> {
+    let center = match value {
+        "red" => Some(0.0),
+        "yellow" => Some(60.0),
+        "green" => Some(120.0),
+        "cyan" => Some(180.0),
+        "blue" => Some(240.0),
+        "magenta" => Some(300.0),
+        _ => None,
+    };
+    if let Some(center) = center {
+        return Ok((center - 30.0, center + 30.0));
+    }
+
+    value
+        .split_once(',')
+        .and_then(|(from, to)| Some((from.trim().parse().ok()?, to.trim().parse().ok()?)))
+        .filter(|(from, to): &(f32, f32)| from.is_finite() && to.is_finite())
+        .ok_or_else(|| format!("`{value}` is neither from,to degrees nor a color name"))
+}
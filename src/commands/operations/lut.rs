@@ -0,0 +1,73 @@
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{arg, ArgAction, ArgMatches, Command};
+use climp::lut::{parse_cube, Lut as Table, LutInterpolation};
+use climp::{apply_lut, map_image, AnyImage, ClimpError};
+use std::fs;
+
+use crate::commands::operation::{arg, parse_factor, Operation};
+
+pub struct Lut;
+
+impl Operation for Lut {
+    fn name(&self) -> &'static str {
+        "lut"
+    }
+
+    fn label(&self) -> &'static str {
+        "Graded"
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about("Grade colors by a 1D or 3D .cube lookup table")
+            .arg_required_else_help(true)
+            .arg(
+                arg!(--cube <FILE> "The .cube file, as exported by Resolve, Premiere or Photoshop")
+                    .value_parser(parse_cube_arg)
+                    .required(true)
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(-i --interpolation <METHOD> "How 3D tables are sampled between their points")
+                    .value_parser(
+                        PossibleValuesParser::new(["trilinear", "tetrahedral"])
+                            .map(|s| s.parse::<LutInterpolation>().unwrap()),
+                    )
+                    .default_value("trilinear")
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(-s --strength <VALUE> "How much of the grade is applied, from 0 to 1 or a percentage")
+                    .value_parser(parse_strength)
+                    .default_value("1")
+                    .action(ArgAction::Set),
+            )
+    }
+
+    fn apply(&self, img: &AnyImage, matches: &ArgMatches) -> Result<AnyImage, ClimpError> {
+        let table: Table = arg(matches, "cube")?;
+        let interpolation = arg(matches, "interpolation")?;
+        let strength = arg(matches, "strength")?;
+
+        Ok(map_image!(img, |buf| apply_lut(
+            buf,
+            &table,
+            interpolation,
+            strength
+        )))
+    }
+}
+
+fn parse_cube_arg(value: &str) -> Result<Table, String> {
+    let text = fs::read_to_string(value).map_err(|e| format!("can't read `{value}`: {e}"))?;
+    parse_cube(&text).map_err(|e| format!("{value}: {e}"))
+}
+
+/// Parses strength from 0 to 1 or a percentage
+fn parse_strength(value: &str) -> Result<f32, String> {
+    parse_factor(value)
+        .ok()
+        .filter(|factor| (0.0..=1.0).contains(factor))
+        .map(|factor| factor as f32)
+        .ok_or_else(|| format!("`{value}` is not a number from 0 to 1 or a percentage up to 100%"))
+}
@@ -0,0 +1,24 @@
+use clap::{ArgMatches, Command};
+use climp::{map_image, mirror, AnyImage, ClimpError};
+
+use crate::commands::operation::Operation;
+
+pub struct Mirror;
+
+impl Operation for Mirror {
+    fn name(&self) -> &'static str {
+        "mirror"
+    }
+
+    fn label(&self) -> &'static str {
+        "Mirrored"
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.name()).about("Mirror the image")
+    }
+
+    fn apply(&self, img: &AnyImage, _matches: &ArgMatches) -> Result<AnyImage, ClimpError> {
+        Ok(map_image!(img, |buf| mirror(buf)))
+    }
+}
@@ -0,0 +1,53 @@
+mod adjust;
+mod blur;
+mod border;
+mod clahe;
+mod crop;
+mod curse;
+mod dither;
+mod equalize;
+mod flip_vertical;
+mod grayscale;
+mod hsl;
+mod lut;
+mod mirror;
+mod monochrome_ugly;
+mod pad;
+mod pixelate;
+mod quantize;
+mod rotate;
+mod scale;
+mod trim;
+mod zxc;
+
+use super::operation::Operation;
+
+/// Registry of every image operation in the order they are listed in help
+pub const OPERATIONS: &[&dyn Operation] = &[
+    &pixelate::Pixelate,
+    &blur::Blur,
+    &mirror::Mirror,
+    &flip_vertical::FlipVertical,
+    &rotate::Rotate,
+    &crop::Crop,
+    &trim::Trim,
+    &pad::Pad,
+    &border::Border,
+    &grayscale::Grayscale,
+    &monochrome_ugly::MonochromeUgly,
+    &dither::Dither,
+    &quantize::Quantize,
+    &adjust::Adjust,
+    &hsl::Hsl,
+    &lut::Lut,
+    &equalize::Equalize,
+    &clahe::Clahe,
+    &scale::Scale,
+    &curse::Curse,
+    &zxc::Zxc,
+];
+
+/// Finds operation by its subcommand name
+pub fn find(name: &str) -> Option<&'static dyn Operation> {
+    OPERATIONS.iter().copied().find(|op| op.name() == name)
+}
@@ -0,0 +1,78 @@
+use clap::{arg, value_parser, ArgAction, ArgMatches, Command};
+use climp::{map_image, monochrome_ugly, AnyImage, ClimpError, Threshold};
+
+use crate::commands::operation::{arg, background_arg, luminance_arg, with_background, Operation};
+
+pub struct MonochromeUgly;
+
+impl Operation for MonochromeUgly {
+    fn name(&self) -> &'static str {
+        "monochrome_ugly"
+    }
+
+    fn label(&self) -> &'static str {
+        "Monochrome"
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about("Make the image monochrome")
+            .arg(
+                arg!(-t --threshold <VALUE> "Luminance threshold from 0 to 255, auto picks it by Otsu's method")
+                    .default_value("128.0")
+                    .value_parser(parse_threshold)
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--adaptive <METHOD> "Threshold every pixel by the mean or gaussian weighted mean of its --window")
+                    .value_parser(["mean", "gaussian"])
+                    .conflicts_with("threshold")
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--window <PIXELS> "Side of the square around the pixel of --adaptive threshold")
+                    .default_value("31")
+                    .value_parser(value_parser!(u32).range(1..))
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--offset <VALUE> "Amount subtracted from the --adaptive threshold, keeps flat paper white")
+                    .default_value("10")
+                    .value_parser(value_parser!(f32))
+                    .allow_negative_numbers(true)
+                    .action(ArgAction::Set),
+            )
+            .arg(luminance_arg())
+            .arg(background_arg())
+    }
+
+    fn apply(&self, img: &AnyImage, matches: &ArgMatches) -> Result<AnyImage, ClimpError> {
+        let threshold = match matches.get_one::<String>("adaptive").map(String::as_str) {
+            Some(method) => {
+                let window = arg(matches, "window")?;
+                let offset = arg(matches, "offset")?;
+                if method == "mean" {
+                    Threshold::Mean { window, offset }
+                } else {
+                    Threshold::Gaussian { window, offset }
+                }
+            }
+            None => arg(matches, "threshold")?,
+        };
+        let luminance = arg(matches, "luminance")?;
+        Ok(map_image!(&with_background(img, matches), |buf| {
+            monochrome_ugly(buf, threshold, luminance)
+        }))
+    }
+}
+
+/// Parses a fixed threshold or `auto`
+fn parse_threshold(value: &str) -> Result<Threshold, String> {
+    if value == "auto" {
+        return Ok(Threshold::Otsu);
+    }
+    value
+        .parse::<f32>()
+        .map(Threshold::Fixed)
+        .map_err(|_| format!("`{value}` is neither a number nor auto"))
+}
@@ -0,0 +1,120 @@
+use clap::{arg, value_parser, ArgAction, ArgGroup, ArgMatches, Command};
+use climp::{
+    map_image, pad, padding_to_aspect, padding_to_size, parse_color, AnyImage, ClimpError, PadFill,
+};
+use image::Rgba;
+
+use crate::commands::operation::{arg, checked_padding, gravity_arg, parse_aspect, Operation};
+
+pub struct Pad;
+
+impl Operation for Pad {
+    fn name(&self) -> &'static str {
+        "pad"
+    }
+
+    fn label(&self) -> &'static str {
+        "Padded"
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about("Extend the canvas by given amounts or to a size or aspect ratio")
+            .arg_required_else_help(true)
+            .arg(
+                arg!(--by <PIXELS> "Padding of all sides, vertical,horizontal sides or top,right,bottom,left")
+                    .value_parser(parse_sides)
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--size <SIZE> "Target canvas size as WIDTHxHEIGHT, e.g. 1080x1080")
+                    .value_parser(parse_size)
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--aspect <RATIO> "Target aspect ratio of the canvas, e.g. 16:9 or 1.5")
+                    .value_parser(parse_aspect)
+                    .action(ArgAction::Set),
+            )
+            .group(
+                ArgGroup::new("amount")
+                    .args(["by", "size", "aspect"])
+                    .required(true),
+            )
+            .arg(gravity_arg("Where the image is placed on the --size or --aspect canvas").conflicts_with("by"))
+            .arg(
+                arg!(--fill <FILL> "Color of the new area, e.g. white or #ff8800, transparent, edge to stretch edge pixels or blur for a blurred copy of the image")
+                    .value_parser(parse_fill)
+                    .default_value("transparent")
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--blur_radius <VALUE> "Blur radius of --fill blur")
+                    .default_value("20")
+                    .value_parser(value_parser!(u32))
+                    .action(ArgAction::Set),
+            )
+    }
+
+    fn apply(&self, img: &AnyImage, matches: &ArgMatches) -> Result<AnyImage, ClimpError> {
+        let dims = img.dimensions();
+        let padding = if let Some(&[top, right, bottom, left]) = matches.get_one::<[u32; 4]>("by") {
+            (left, top, right, bottom)
+        } else if let Some(&size) = matches.get_one::<(u32, u32)>("size") {
+            padding_to_size(dims, size, arg(matches, "gravity")?)
+        } else {
+            padding_to_aspect(dims, arg(matches, "aspect")?, arg(matches, "gravity")?)
+        };
+        let padding = checked_padding(img, padding)?;
+        let fill = match arg(matches, "fill")? {
+            Fill::Color(color) => PadFill::Color(color),
+            Fill::Edge => PadFill::Edge,
+            Fill::Blur => PadFill::Blur(arg(matches, "blur_radius")?),
+        };
+
+        Ok(map_image!(img, |buf| pad(buf, padding, fill)))
+    }
+}
+
+/// `--fill` value, radius of the blur is given on its own
+#[derive(Debug, Clone, Copy)]
+enum Fill {
+    Color(Rgba<u8>),
+    Edge,
+    Blur,
+}
+
+fn parse_fill(value: &str) -> Result<Fill, String> {
+    match value {
+        "edge" => Ok(Fill::Edge),
+        "blur" => Ok(Fill::Blur),
+        color => parse_color(color)
+            .map(Fill::Color)
+            .map_err(|e| format!("{e}, edge or blur")),
+    }
+}
+
+/// Parses CSS-like sides: one value for all of them, two for vertical and horizontal ones or four
+/// going clockwise from the top
+fn parse_sides(value: &str) -> Result<[u32; 4], String> {
+    let sides = value
+        .split(',')
+        .map(|side| side.trim().parse::<u32>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("`{value}` must be a list of pixels"))?;
+    match sides[..] {
+        [all] => Ok([all; 4]),
+        [vertical, horizontal] => Ok([vertical, horizontal, vertical, horizontal]),
+        [top, right, bottom, left] => Ok([top, right, bottom, left]),
+        _ => Err(format!("`{value}` must have 1, 2 or 4 sides")),
+    }
+}
+
+/// Parses `WIDTHxHEIGHT`
+fn parse_size(value: &str) -> Result<(u32, u32), String> {
+    value
+        .split_once('x')
+        .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)))
+        .filter(|&(w, h)| w > 0 && h > 0)
+        .ok_or_else(|| format!("`{value}` is not a size like 1080x1080"))
+}
@@ -0,0 +1,36 @@
+use clap::{arg, value_parser, ArgAction, ArgMatches, Command};
+use climp::{map_image, pixelate, AnyImage, ClimpError};
+
+use crate::commands::operation::{arg, background_arg, with_background, Operation};
+
+pub struct Pixelate;
+
+impl Operation for Pixelate {
+    fn name(&self) -> &'static str {
+        "pixelate"
+    }
+
+    fn label(&self) -> &'static str {
+        "Pixelated"
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about("Pixelate the image with a given pixel size")
+            .arg_required_else_help(true)
+            .arg(
+                arg!(-p --pixel_size <VALUE>)
+                    .required(true)
+                    .value_parser(value_parser!(u32).range(1..))
+                    .action(ArgAction::Set),
+            )
+            .arg(background_arg())
+    }
+
+    fn apply(&self, img: &AnyImage, matches: &ArgMatches) -> Result<AnyImage, ClimpError> {
+        let size: u32 = arg(matches, "pixel_size")?;
+        Ok(map_image!(&with_background(img, matches), |buf| {
+            pixelate(buf, (size, size))
+        }))
+    }
+}
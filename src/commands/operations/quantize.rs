@@ -0,0 +1,91 @@
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{arg, value_parser, ArgAction, ArgGroup, ArgMatches, Command};
+use climp::palette::{builtin, parse_palette, PaletteMethod};
+use climp::{extract_palette, map_image, quantize, AnyImage, ClimpError, Dither};
+use image::Rgb;
+use std::fs;
+
+use crate::commands::operation::{arg, dither_parser, Operation};
+
+pub struct Quantize;
+
+impl Operation for Quantize {
+    fn name(&self) -> &'static str {
+        "quantize"
+    }
+
+    fn label(&self) -> &'static str {
+        "Quantized"
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about("Reduce the image to a number of colors or to a palette")
+            .arg_required_else_help(true)
+            .arg(
+                arg!(-c --colors <COUNT> "Number of colors picked from the image")
+                    .value_parser(value_parser!(u16).range(1..=256))
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--palette <PALETTE> "Built-in palette cga, gameboy, pico-8 or web-safe, or a .gpl or hex list file")
+                    .value_parser(parse_palette_arg)
+                    .action(ArgAction::Set),
+            )
+            .group(
+                ArgGroup::new("target")
+                    .args(["colors", "palette"])
+                    .required(true),
+            )
+            .arg(
+                arg!(--method <METHOD> "How --colors are picked")
+                    .value_parser(
+                        PossibleValuesParser::new(["median-cut", "k-means"])
+                            .map(|s| s.parse::<PaletteMethod>().unwrap()),
+                    )
+                    .default_value("median-cut")
+                    .conflicts_with("palette")
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--dither <METHOD> "Dither the colors, by error diffusion or a Bayer matrix")
+                    .value_parser(dither_parser())
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--serpentine "Scan every other row backwards while dithering")
+                    .action(ArgAction::SetTrue),
+            )
+    }
+
+    fn apply(&self, img: &AnyImage, matches: &ArgMatches) -> Result<AnyImage, ClimpError> {
+        let palette = match matches.get_one::<Vec<Rgb<u8>>>("palette") {
+            Some(palette) => palette.clone(),
+            None => {
+                let count = arg::<u16>(matches, "colors")? as usize;
+                let method = arg(matches, "method")?;
+                match img {
+                    AnyImage::Rgba8(buf) => extract_palette(buf, count, method),
+                    AnyImage::Rgba16(buf) => extract_palette(buf, count, method),
+                    AnyImage::Rgba32F(buf) => extract_palette(buf, count, method),
+                }
+            }
+        };
+        let dither = matches.get_one::<Dither>("dither").copied();
+        let serpentine = matches.get_flag("serpentine");
+
+        Ok(map_image!(img, |buf| quantize(
+            buf, &palette, dither, serpentine
+        )))
+    }
+}
+
+/// Gets a built-in palette by its name or reads a palette file
+fn parse_palette_arg(value: &str) -> Result<Vec<Rgb<u8>>, String> {
+    if let Some(palette) = builtin(value) {
+        return Ok(palette);
+    }
+    let text = fs::read_to_string(value)
+        .map_err(|e| format!("`{value}` is neither a built-in palette nor a readable file: {e}"))?;
+    parse_palette(&text).map_err(|e| format!("{value}: {e}"))
+}
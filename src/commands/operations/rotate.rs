@@ -0,0 +1,81 @@
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{arg, value_parser, ArgAction, ArgMatches, Command};
+use climp::{
+    map_image, parse_color, rotate_by, rotated_dimensions, AnyImage, ClimpError, Interpolation,
+};
+use image::Rgba;
+
+use crate::commands::operation::{arg, check_canvas, Operation};
+
+pub struct Rotate;
+
+impl Operation for Rotate {
+    fn name(&self) -> &'static str {
+        "rotate"
+    }
+
+    fn label(&self) -> &'static str {
+        "Rotated"
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about("Rotate an image clockwise, 90 degrees by default")
+            .arg(
+                arg!(-d --degrees <DEGREES> "Angle of the clockwise rotation, negative turns counterclockwise")
+                    .value_parser(value_parser!(f32))
+                    .allow_negative_numbers(true)
+                    .default_value("90")
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--interpolation <INTERPOLATION> "Interpolation of angles other than multiples of 90")
+                    .value_parser(
+                        PossibleValuesParser::new(["nearest", "bilinear", "bicubic"])
+                            .map(|s| s.parse::<Interpolation>().unwrap()),
+                    )
+                    .default_value("bilinear")
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--canvas <CANVAS> "Expand the canvas to fit the rotated image or crop it to the original size")
+                    .value_parser(["expand", "crop"])
+                    .default_value("expand")
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--background <COLOR> "Color of the uncovered areas, e.g. white or #ff8800")
+                    .value_parser(parse_color)
+                    .default_value("transparent")
+                    .action(ArgAction::Set),
+            )
+    }
+
+    fn apply(&self, img: &AnyImage, matches: &ArgMatches) -> Result<AnyImage, ClimpError> {
+        let degrees: f32 = arg(matches, "degrees")?;
+        if !degrees.is_finite() {
+            return Err(ClimpError::InvalidArgument(format!(
+                "can't rotate by {degrees} degrees"
+            )));
+        }
+        let interpolation = arg(matches, "interpolation")?;
+        let expand = arg::<String>(matches, "canvas")? == "expand";
+        let background: Rgba<u8> = arg(matches, "background")?;
+        if expand {
+            let (width, height) = rotated_dimensions(img.dimensions(), degrees);
+            check_canvas(
+                img,
+                (width as u64, height as u64),
+                &format!("rotating by {degrees} degrees"),
+            )?;
+        }
+
+        Ok(map_image!(img, |buf| rotate_by(
+            buf,
+            degrees,
+            interpolation,
+            expand,
+            background
+        )))
+    }
+}
@@ -0,0 +1,109 @@
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{arg, value_parser, ArgAction, ArgGroup, ArgMatches, Command};
+use climp::{map_image, scale, scaled_dimensions, AnyImage, ClimpError, ResampleFilter, ScaleMode};
+
+use crate::commands::operation::{arg, check_canvas, parse_factor, Operation};
+
+pub struct Scale;
+
+impl Operation for Scale {
+    fn name(&self) -> &'static str {
+        "scale"
+    }
+
+    fn label(&self) -> &'static str {
+        "Scaled"
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about("Scale the image by a factor or to the given size")
+            .arg_required_else_help(true)
+            .arg(
+                arg!(-s --scale <VALUE> "Scaling factor, e.g. 2 or 0.5, or percentage, e.g. 50%")
+                    .value_parser(parse_scale)
+                    .conflicts_with_all(["width", "height"])
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--width <PIXELS> "Target width, the height follows the aspect ratio unless given")
+                    .value_parser(value_parser!(u32).range(1..))
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--height <PIXELS> "Target height, the width follows the aspect ratio unless given")
+                    .value_parser(value_parser!(u32).range(1..))
+                    .action(ArgAction::Set),
+            )
+            .group(
+                ArgGroup::new("size")
+                    .args(["scale", "width", "height"])
+                    .multiple(true)
+                    .required(true),
+            )
+            .arg(
+                arg!(--mode <MODE> "How both --width and --height are met: fit inside, fill and crop the overflow, or stretch exactly")
+                    .value_parser(
+                        PossibleValuesParser::new(["fit", "fill", "exact"])
+                            .map(|s| s.parse::<ScaleMode>().unwrap()),
+                    )
+                    .default_value("fit")
+                    .conflicts_with("scale")
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                arg!(--filter <FILTER> "Resampling filter")
+                    .value_parser(
+                        PossibleValuesParser::new([
+                            "nearest",
+                            "triangle",
+                            "catmull-rom",
+                            "gaussian",
+                            "lanczos3",
+                        ])
+                        .map(|s| s.parse::<ResampleFilter>().unwrap()),
+                    )
+                    .default_value("lanczos3")
+                    .action(ArgAction::Set),
+            )
+    }
+
+    fn apply(&self, img: &AnyImage, matches: &ArgMatches) -> Result<AnyImage, ClimpError> {
+        let (width, height) = img.dimensions();
+        let new_dims = match matches.get_one::<f64>("scale") {
+            Some(factor) if (factor * width as f64) < 0.5 || (factor * height as f64) < 0.5 => {
+                return Err(ClimpError::InvalidArgument(format!(
+                    "scale {factor} turns {width}x{height} image into nothing"
+                )))
+            }
+            Some(factor) => (
+                Some((factor * width as f64).round() as u32),
+                Some((factor * height as f64).round() as u32),
+            ),
+            None => (
+                matches.get_one::<u32>("width").copied(),
+                matches.get_one::<u32>("height").copied(),
+            ),
+        };
+        // Both sides of a factor are rounded on their own, so they are met exactly
+        let mode = if matches.contains_id("scale") {
+            ScaleMode::Exact
+        } else {
+            arg(matches, "mode")?
+        };
+        let filter = arg::<ResampleFilter>(matches, "filter")?.into();
+        // `Fill` is resampled to its uncropped size first
+        let (new_width, new_height) = scaled_dimensions((width, height), new_dims, mode);
+        check_canvas(img, (new_width as u64, new_height as u64), "scaling")?;
+
+        Ok(map_image!(img, |buf| scale(buf, new_dims, mode, filter)))
+    }
+}
+
+/// Parses positive factor or percentage
+fn parse_scale(value: &str) -> Result<f64, String> {
+    parse_factor(value)
+        .ok()
+        .filter(|&factor| factor > 0.0)
+        .ok_or_else(|| format!("`{value}` is not a positive number or percentage"))
+}
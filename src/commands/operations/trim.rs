@@ -0,0 +1,48 @@
+use clap::{arg, value_parser, ArgAction, ArgMatches, Command};
+use climp::{crop, map_image, trim_box, AnyImage, ClimpError};
+
+use crate::commands::operation::{arg, Operation};
+
+pub struct Trim;
+
+impl Operation for Trim {
+    fn name(&self) -> &'static str {
+        "trim"
+    }
+
+    fn label(&self) -> &'static str {
+        "Trimmed"
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about("Trim border of near-constant color, e.g. margins of scans")
+            .arg(
+                arg!(-t --tolerance <VALUE> "Max difference from the corner color in every channel, 0 to 255")
+                    .default_value("10")
+                    .value_parser(value_parser!(f32))
+                    .action(ArgAction::Set),
+            )
+    }
+
+    fn apply(&self, img: &AnyImage, matches: &ArgMatches) -> Result<AnyImage, ClimpError> {
+        let tolerance: f32 = arg(matches, "tolerance")?;
+        let (width, height) = img.dimensions();
+        let region = match img {
+            AnyImage::Rgba8(buf) => trim_box(buf, tolerance),
+            AnyImage::Rgba16(buf) => trim_box(buf, tolerance),
+            AnyImage::Rgba32F(buf) => trim_box(buf, tolerance),
+        };
+        // stdout may be taken by the image, so the box is reported to stderr
+        match region {
+            Some((x, y, w, h)) => {
+                eprintln!("Trimmed {width}x{height} image to {w}x{h} at {x},{y}");
+                Ok(map_image!(img, |buf| crop(buf, x, y, w, h)?))
+            }
+            None => {
+                eprintln!("Nothing to trim, the whole {width}x{height} image is a border");
+                Ok(img.clone())
+            }
+        }
+    }
+}
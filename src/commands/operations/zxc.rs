@@ -0,0 +1,26 @@
+use clap::{ArgMatches, Command};
+use climp::{map_image, zxc, AnyImage, ClimpError};
+
+use crate::commands::operation::{background_arg, with_background, Operation};
+
+pub struct Zxc;
+
+impl Operation for Zxc {
+    fn name(&self) -> &'static str {
+        "zxc"
+    }
+
+    fn label(&self) -> &'static str {
+        "ZXCursed"
+    }
+
+    fn command(&self) -> Command {
+        Command::new(self.name())
+            .about("Ultimate zxc dead inside the image")
+            .arg(background_arg())
+    }
+
+    fn apply(&self, img: &AnyImage, matches: &ArgMatches) -> Result<AnyImage, ClimpError> {
+        Ok(map_image!(&with_background(img, matches), |buf| zxc(buf)))
+    }
+}
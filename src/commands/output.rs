@@ -0,0 +1,299 @@
+use clap::{ArgMatches, ValueEnum};
+use climp::{tone_map, AnyImage, ClimpError, ToneMap};
+use image::codecs::hdr::HdrEncoder;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::{self, PngEncoder};
+use image::codecs::pnm::{PnmSubtype, SampleEncoding};
+use image::error::{EncodingError, ImageFormatHint};
+use image::{DynamicImage, ImageError, ImageFormat, ImageOutputFormat};
+use image_webp::{ColorType, WebPEncoder};
+use std::fs::File;
+use std::io::{BufWriter, Cursor, Write};
+use std::path::Path;
+
+/// PNG compression level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Compression {
+    Default,
+    Fast,
+    Best,
+}
+
+/// PNG filter applied to every scanline before compression
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PngFilter {
+    None,
+    Sub,
+    Up,
+    Avg,
+    Paeth,
+    Adaptive,
+}
+
+/// Encoder settings of the output, shared by the command line and recipes
+///
+/// Settings which don't apply to the output format are ignored,
+/// but a bit depth the format can't store is an error
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OutputOptions {
+    pub format: Option<ImageFormat>,
+    pub quality: Option<u8>,
+    pub compression: Option<Compression>,
+    pub png_filter: Option<PngFilter>,
+    pub lossy: bool,
+    pub bit_depth: Option<u8>,
+    pub tone_map: Option<ToneMap>,
+}
+
+impl OutputOptions {
+    /// Collects the options parsed by `output_args`
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        Self {
+            format: matches.get_one::<ImageFormat>("format").copied(),
+            quality: matches.get_one::<u8>("quality").copied(),
+            compression: matches.get_one::<Compression>("compression").copied(),
+            png_filter: matches.get_one::<PngFilter>("png_filter").copied(),
+            lossy: matches.get_flag("lossy"),
+            bit_depth: matches.get_one::<u8>("bit_depth").copied(),
+            tone_map: matches.get_one::<ToneMap>("tone_map").copied(),
+        }
+    }
+
+    /// Fills the options which aren't set with the ones of `other`
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            format: self.format.or(other.format),
+            quality: self.quality.or(other.quality),
+            compression: self.compression.or(other.compression),
+            png_filter: self.png_filter.or(other.png_filter),
+            lossy: self.lossy || other.lossy,
+            bit_depth: self.bit_depth.or(other.bit_depth),
+            tone_map: self.tone_map.or(other.tone_map),
+        }
+    }
+}
+
+/// Parses format from its name listed in `--output` help or from one of its extensions
+pub fn parse_format(value: &str) -> Result<ImageFormat, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "jpeg" => Some(ImageFormat::Jpeg),
+        "pnm" => Some(ImageFormat::Pnm),
+        "openexr" => Some(ImageFormat::OpenExr),
+        ext => ImageFormat::from_extension(ext),
+    }
+    .ok_or_else(|| format!("unsupported format `{value}`"))
+}
+
+/// Resolves format of the output file
+///
+/// `--format` wins, otherwise the format is taken from the file extension.
+/// Formats and bit depths which can't be encoded are reported before any image is processed
+pub fn resolve_format(name: &Path, options: &OutputOptions) -> Result<ImageFormat, ClimpError> {
+    let format = match options.format {
+        Some(format) => format,
+        None => {
+            let ext = name.extension().and_then(std::ffi::OsStr::to_str);
+            ext.and_then(|ext| parse_format(ext).ok()).ok_or_else(|| {
+                ClimpError::UnsupportedFormat(match ext {
+                    Some(ext) => format!("unknown extension `{ext}` of {name:?}, use --format"),
+                    None => format!("{name:?} has no extension, use --format"),
+                })
+            })?
+        }
+    };
+
+    match format {
+        ImageFormat::Dds => {
+            return Err(ClimpError::UnsupportedFormat(String::from(
+                "Dds images can be read but not written",
+            )))
+        }
+        ImageFormat::Avif => {
+            return Err(ClimpError::UnsupportedFormat(String::from(
+                "Avif encoding is not enabled in this build",
+            )))
+        }
+        _ => {}
+    }
+
+    match options.bit_depth {
+        Some(depth) if !bit_depths(format).contains(&depth) => {
+            Err(ClimpError::UnsupportedFormat(format!(
+                "{format:?} can't be written with {depth} bits per channel, supported: {:?}",
+                bit_depths(format)
+            )))
+        }
+        _ => Ok(format),
+    }
+}
+
+/// Bits per channel the format can be written with
+fn bit_depths(format: ImageFormat) -> &'static [u8] {
+    match format {
+        ImageFormat::Png | ImageFormat::Tiff => &[8, 16],
+        ImageFormat::Farbfeld => &[16],
+        ImageFormat::OpenExr | ImageFormat::Hdr => &[32],
+        _ => &[8],
+    }
+}
+
+/// Picks the deepest bit depth of the format which doesn't exceed the one of the image
+fn default_depth(format: ImageFormat, image_depth: u8) -> u8 {
+    let depths = bit_depths(format);
+    depths
+        .iter()
+        .copied()
+        .filter(|&depth| depth <= image_depth)
+        .max()
+        .unwrap_or(depths[0])
+}
+
+/// Encodes the image into `writer`
+///
+/// The image is converted to a color type the encoder accepts,
+/// e.g. Jpeg drops alpha channel and OpenExr is written as 32-bit float.
+/// Without `--bit_depth` the precision of the image is kept as far as the format allows,
+/// float images written with less than 32 bits are tone mapped first
+pub fn encode<W: Write>(
+    img: &AnyImage,
+    writer: &mut W,
+    format: ImageFormat,
+    options: &OutputOptions,
+    name: &Path,
+) -> Result<(), ClimpError> {
+    // Encoders write into memory, so their io errors come from the image, not from the file
+    let error = |e| match e {
+        ImageError::IoError(_) => ClimpError::Encode(name.into(), e),
+        e => ClimpError::encode(name, e),
+    };
+    let depth = options
+        .bit_depth
+        .unwrap_or_else(|| default_depth(format, img.bit_depth()));
+    let img = match img {
+        AnyImage::Rgba32F(buf) if depth < 32 => {
+            DynamicImage::ImageRgba32F(tone_map(buf, options.tone_map.unwrap_or_default()))
+        }
+        img => DynamicImage::from(img.clone()),
+    };
+
+    // Some encoders need to seek, so the image is encoded in memory first
+    let mut bytes = Cursor::new(Vec::new());
+    match format {
+        ImageFormat::WebP => {
+            let rgba = img.into_rgba8();
+            let webp_error = |e: Box<dyn std::error::Error + Send + Sync>| {
+                error(ImageError::Encoding(EncodingError::new(
+                    ImageFormatHint::Exact(ImageFormat::WebP),
+                    e,
+                )))
+            };
+            // image crate can't encode WebP without libwebp,
+            // image-webp writes the lossless one and libwebp the lossy one
+            if options.lossy {
+                let webp = webp::Encoder::from_rgba(&rgba, rgba.width(), rgba.height())
+                    .encode_simple(false, options.quality.unwrap_or(75) as f32)
+                    .map_err(|e| webp_error(format!("{e:?}").into()))?;
+                bytes.get_mut().extend_from_slice(&webp);
+            } else {
+                WebPEncoder::new(&mut bytes)
+                    .encode(&rgba, rgba.width(), rgba.height(), ColorType::Rgba8)
+                    .map_err(|e| webp_error(e.into()))?;
+            }
+        }
+        ImageFormat::Hdr => {
+            let rgb = img.into_rgb32f();
+            let pixels = rgb.pixels().copied().collect::<Vec<_>>();
+            HdrEncoder::new(&mut bytes)
+                .encode(&pixels, rgb.width() as usize, rgb.height() as usize)
+                .map_err(error)?;
+        }
+        ImageFormat::Jpeg => {
+            let encoder = JpegEncoder::new_with_quality(&mut bytes, options.quality.unwrap_or(75));
+            DynamicImage::ImageRgb8(img.into_rgb8())
+                .write_with_encoder(encoder)
+                .map_err(error)?;
+        }
+        ImageFormat::Png => {
+            let compression = match options.compression.unwrap_or(Compression::Default) {
+                Compression::Default => png::CompressionType::Default,
+                Compression::Fast => png::CompressionType::Fast,
+                Compression::Best => png::CompressionType::Best,
+            };
+            let filter = match options.png_filter.unwrap_or(PngFilter::Adaptive) {
+                PngFilter::None => png::FilterType::NoFilter,
+                PngFilter::Sub => png::FilterType::Sub,
+                PngFilter::Up => png::FilterType::Up,
+                PngFilter::Avg => png::FilterType::Avg,
+                PngFilter::Paeth => png::FilterType::Paeth,
+                PngFilter::Adaptive => png::FilterType::Adaptive,
+            };
+            with_depth(img, depth)
+                .write_with_encoder(PngEncoder::new_with_quality(
+                    &mut bytes,
+                    compression,
+                    filter,
+                ))
+                .map_err(error)?;
+        }
+        format => {
+            let output_format = match format {
+                ImageFormat::Pnm => ImageOutputFormat::Pnm(pnm_subtype(name)),
+                format => ImageOutputFormat::from(format),
+            };
+            let img = match output_format {
+                ImageOutputFormat::Pnm(PnmSubtype::Pixmap(_)) => {
+                    DynamicImage::ImageRgb8(img.into_rgb8())
+                }
+                ImageOutputFormat::Pnm(PnmSubtype::Graymap(_)) => {
+                    DynamicImage::ImageLuma8(img.into_luma8())
+                }
+                // Bitmap encoder writes black for zero samples only
+                ImageOutputFormat::Pnm(PnmSubtype::Bitmap(_)) => {
+                    let mut luma = img.into_luma8();
+                    luma.pixels_mut()
+                        .for_each(|p| p[0] = if p[0] < 128 { 0 } else { 255 });
+                    DynamicImage::ImageLuma8(luma)
+                }
+                _ => with_depth(img, depth),
+            };
+            img.write_to(&mut bytes, output_format).map_err(error)?;
+        }
+    }
+
+    writer
+        .write_all(bytes.get_ref())
+        .map_err(|e| ClimpError::Io(name.into(), e))
+}
+
+/// Converts the image to RGBA with the given bits per channel
+fn with_depth(img: DynamicImage, depth: u8) -> DynamicImage {
+    match depth {
+        16 => DynamicImage::ImageRgba16(img.into_rgba16()),
+        32 => DynamicImage::ImageRgba32F(img.into_rgba32f()),
+        _ => DynamicImage::ImageRgba8(img.into_rgba8()),
+    }
+}
+
+/// Picks Pnm flavor by the file extension, `.pnm` is written as a pixmap,
+/// `.pam` and stdout keep alpha channel
+fn pnm_subtype(name: &Path) -> PnmSubtype {
+    match name.extension().and_then(std::ffi::OsStr::to_str) {
+        Some("pbm") => PnmSubtype::Bitmap(SampleEncoding::Binary),
+        Some("pgm") => PnmSubtype::Graymap(SampleEncoding::Binary),
+        Some("ppm" | "pnm") => PnmSubtype::Pixmap(SampleEncoding::Binary),
+        _ => PnmSubtype::ArbitraryMap,
+    }
+}
+
+/// Encodes the image into the file at `name`
+pub fn save(
+    img: &AnyImage,
+    name: &Path,
+    format: ImageFormat,
+    options: &OutputOptions,
+) -> Result<(), ClimpError> {
+    let file = File::create(name).map_err(|e| ClimpError::Io(name.into(), e))?;
+    let mut writer = BufWriter::new(file);
+    encode(img, &mut writer, format, options, name)?;
+    writer.flush().map_err(|e| ClimpError::Io(name.into(), e))
+}
@@ -0,0 +1,107 @@
+use clap::{ArgMatches, ValueEnum};
+use climp::palette::{swatch, to_ase, to_gpl, to_hex, PaletteMethod};
+use climp::{dominant_colors, AnyImage, ClimpError};
+use image::{ImageError, ImageOutputFormat, Rgb};
+use serde_json::json;
+use std::fs;
+use std::io::{self, Cursor, Write};
+use std::path::{Path, PathBuf};
+
+use super::operation::arg;
+
+/// Side of a swatch square, stripes of the colors share the width of a square per color
+const SWATCH_SIZE: u32 = 64;
+
+/// What `palette` writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Report {
+    /// Lines of hex colors with their shares
+    Hex,
+    /// Array of colors with their hex, rgb and share
+    Json,
+    /// PNG of stripes as wide as the shares of the colors
+    Swatch,
+    /// GIMP palette
+    Gpl,
+    /// Adobe Swatch Exchange palette
+    Ase,
+}
+
+impl Report {
+    /// Extension of the file the report is saved to
+    pub fn extension(self) -> &'static str {
+        match self {
+            Report::Hex => "txt",
+            Report::Json => "json",
+            Report::Swatch => "png",
+            Report::Gpl => "gpl",
+            Report::Ase => "ase",
+        }
+    }
+
+    /// Reports meant to be read are printed to stdout unless an output is given,
+    /// palette files are saved next to the input
+    pub fn is_printed(self) -> bool {
+        matches!(self, Report::Hex | Report::Json)
+    }
+}
+
+/// Reports dominant colors of the image to `output`, `-` prints them to stdout
+///
+/// `name` is the palette name written into .gpl files
+pub fn report_palette(
+    img: &AnyImage,
+    matches: &ArgMatches,
+    output: &Path,
+    name: &str,
+) -> Result<(), ClimpError> {
+    let count = arg::<u16>(matches, "colors")? as usize;
+    let method: PaletteMethod = arg(matches, "method")?;
+    let report: Report = arg(matches, "report")?;
+
+    let colors = match img {
+        AnyImage::Rgba8(buf) => dominant_colors(buf, count, method),
+        AnyImage::Rgba16(buf) => dominant_colors(buf, count, method),
+        AnyImage::Rgba32F(buf) => dominant_colors(buf, count, method),
+    };
+    let palette = colors.iter().map(|&(color, _)| color).collect::<Vec<_>>();
+
+    let bytes = match report {
+        Report::Hex => colors
+            .iter()
+            .map(|(color, share)| format!("{} {:5.1}%\n", to_hex(color), share * 100.0))
+            .collect::<String>()
+            .into_bytes(),
+        Report::Json => {
+            let entries = colors
+                .iter()
+                .map(|(color, share)| json!({ "hex": to_hex(color), "rgb": color.0, "share": share }))
+                .collect::<Vec<_>>();
+            (serde_json::to_string_pretty(&entries).unwrap() + "\n").into_bytes()
+        }
+        Report::Swatch => encode_swatch(&colors, output)?,
+        Report::Gpl => to_gpl(&palette, name).into_bytes(),
+        Report::Ase => to_ase(&palette),
+    };
+
+    if output == Path::new("-") {
+        let mut stdout = io::stdout();
+        return stdout
+            .write_all(&bytes)
+            .and_then(|_| stdout.flush())
+            .map_err(|e| ClimpError::Io(PathBuf::from("stdout"), e));
+    }
+    fs::write(output, bytes).map_err(|e| ClimpError::Io(output.into(), e))?;
+    println!("Palette of {} colors saved as {output:?}", palette.len());
+    Ok(())
+}
+
+/// Renders the swatch as PNG
+fn encode_swatch(colors: &[(Rgb<u8>, f32)], output: &Path) -> Result<Vec<u8>, ClimpError> {
+    let width = SWATCH_SIZE * colors.len().max(1) as u32;
+    let mut png = Cursor::new(Vec::new());
+    swatch(colors, width, SWATCH_SIZE)
+        .write_to(&mut png, ImageOutputFormat::Png)
+        .map_err(|e: ImageError| ClimpError::encode(output, e))?;
+    Ok(png.into_inner())
+}
@@ -0,0 +1,175 @@
+use clap::{ArgMatches, Command};
+use climp::ClimpError;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::command_creator::{output_args, step_command, TERMINAL};
+use super::output::OutputOptions;
+
+/// Processing pipeline described by a TOML or JSON file
+///
+/// ```toml
+/// inputs = ["photos/*.jpg"]
+/// recursive = false
+///
+/// [output]
+/// dir = "edited"
+/// format = "png"
+/// quality = 90
+///
+/// [[steps]]
+/// op = "rotate"
+///
+/// [[steps]]
+/// op = "blur"
+/// blur_radius = 3
+/// ```
+///
+/// Step parameters are named after the subcommand arguments and parsed by the same subcommands,
+/// so every step is validated before any image is touched.
+/// The same goes for `[output]` options, except `path` and `dir`, which are named after `--output` and `--out-dir`
+#[derive(Debug, Default)]
+pub struct Recipe {
+    pub inputs: Vec<PathBuf>,
+    pub recursive: bool,
+    pub output: Option<PathBuf>,
+    pub out_dir: Option<PathBuf>,
+    pub output_options: OutputOptions,
+    pub steps: Vec<(String, ArgMatches)>,
+}
+
+impl Recipe {
+    /// Reads and validates the recipe, files with `.json` extension are parsed as JSON
+    pub fn load(path: &Path) -> Result<Self, ClimpError> {
+        let text = fs::read_to_string(path).map_err(|e| ClimpError::Io(path.into(), e))?;
+        let error = |msg: String| ClimpError::InvalidArgument(format!("recipe {path:?}: {msg}"));
+
+        let value: Value = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&text).map_err(|e| error(e.to_string()))?
+        } else {
+            toml::from_str(&text).map_err(|e| error(e.to_string()))?
+        };
+        let Value::Object(table) = value else {
+            return Err(error(String::from("expected a table at the top level")));
+        };
+
+        let mut recipe = Recipe::default();
+        for (key, value) in &table {
+            match key.as_str() {
+                "inputs" => recipe.inputs = strings(value).ok_or_else(|| error(expected(key)))?,
+                "recursive" => {
+                    recipe.recursive = value.as_bool().ok_or_else(|| error(expected(key)))?
+                }
+                "output" => recipe.read_output(value).map_err(error)?,
+                "steps" => {
+                    let steps = value.as_array().ok_or_else(|| error(expected(key)))?;
+                    for (i, step) in steps.iter().enumerate() {
+                        let step = parse_step(step)
+                            .map_err(|msg| error(format!("step {}: {msg}", i + 1)))?;
+                        recipe.steps.push(step);
+                    }
+                }
+                _ => return Err(error(format!("unknown key `{key}`"))),
+            }
+        }
+
+        if recipe.steps.is_empty() {
+            return Err(error(String::from("no steps to run")));
+        }
+        if let Some(i) = recipe.steps[..recipe.steps.len() - 1]
+            .iter()
+            .position(|(name, _)| TERMINAL.contains(&name.as_str()))
+        {
+            return Err(error(format!(
+                "step {}: {} can only be the last step",
+                i + 1,
+                recipe.steps[i].0
+            )));
+        }
+
+        Ok(recipe)
+    }
+
+    fn read_output(&mut self, value: &Value) -> Result<(), String> {
+        let table = value.as_object().ok_or_else(|| expected("output"))?;
+        let mut args = Vec::new();
+        for (key, value) in table {
+            match (key.as_str(), value) {
+                ("path", Value::String(s)) => self.output = Some(PathBuf::from(s)),
+                ("dir", Value::String(s)) => self.out_dir = Some(PathBuf::from(s)),
+                ("path" | "dir", _) => return Err(expected(&format!("output.{key}"))),
+                _ => push_arg(&mut args, key, value).map_err(|e| format!("output.{e}"))?,
+            }
+        }
+
+        let matches = Command::new("output")
+            .no_binary_name(true)
+            .args(output_args())
+            .try_get_matches_from(args)
+            .map_err(|e| format!("output: {}", clap_message(&e)))?;
+        self.output_options = OutputOptions::from_matches(&matches);
+        Ok(())
+    }
+}
+
+/// Turns a step table into arguments of its subcommand and parses them
+fn parse_step(step: &Value) -> Result<(String, ArgMatches), String> {
+    let table = step.as_object().ok_or_else(|| expected("step"))?;
+    let name = table
+        .get("op")
+        .and_then(Value::as_str)
+        .ok_or_else(|| String::from("missing `op` name of the operation"))?;
+    let command = step_command(name).ok_or_else(|| format!("unknown operation `{name}`"))?;
+
+    let mut args = vec![name.to_string()];
+    for (key, value) in table.iter().filter(|(key, _)| *key != "op") {
+        push_arg(&mut args, key, value).map_err(|e| format!("{name}: {e}"))?;
+    }
+
+    command
+        .try_get_matches_from(args)
+        .map(|matches| (name.to_string(), matches))
+        .map_err(|e| format!("{name}: {}", clap_message(&e)))
+}
+
+/// Turns a table entry into a command line argument
+///
+/// `true` becomes a flag, `false` is omitted and other values are passed as they are
+fn push_arg(args: &mut Vec<String>, key: &str, value: &Value) -> Result<(), String> {
+    match value {
+        Value::Bool(true) => args.push(format!("--{key}")),
+        Value::Bool(false) => {}
+        Value::Number(n) => args.extend([format!("--{key}"), n.to_string()]),
+        Value::String(s) => args.extend([format!("--{key}"), s.clone()]),
+        _ => return Err(expected(key)),
+    }
+    Ok(())
+}
+
+/// First line of the clap error without the `error:` prefix
+fn clap_message(e: &clap::Error) -> String {
+    let rendered = e.render().to_string();
+    let line = rendered.lines().next().unwrap_or_default();
+    line.trim_start_matches("error: ").to_string()
+}
+
+fn strings(value: &Value) -> Option<Vec<PathBuf>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|v| v.as_str().map(PathBuf::from))
+        .collect()
+}
+
+fn expected(key: &str) -> String {
+    let kind = match key {
+        "inputs" => "a list of paths",
+        "recursive" => "true or false",
+        "output" | "step" => "a table",
+        "steps" => "a list of tables",
+        "output.path" | "output.dir" => "a string",
+        _ => "a number, string or boolean",
+    };
+    format!("`{key}` must be {kind}")
+}
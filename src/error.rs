@@ -0,0 +1,83 @@
+use image::ImageError;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Errors that can happen while processing images
+///
+/// Every variant maps to its own process exit code via `exit_code` method
+#[derive(Debug)]
+pub enum ClimpError {
+    /// Input file couldn't be decoded as an image
+    Decode(PathBuf, ImageError),
+    /// Result couldn't be encoded into the output file
+    Encode(PathBuf, ImageError),
+    /// Image format isn't supported for reading or writing
+    UnsupportedFormat(String),
+    /// Arguments are valid for the parser but can't be used together
+    InvalidArgument(String),
+    /// File system error on the given path
+    Io(PathBuf, io::Error),
+    /// Some files of a batch failed, each of them has been reported already
+    Batch { failed: usize, total: usize },
+}
+
+impl ClimpError {
+    /// Wraps decoding error of the file at `path`
+    pub fn decode(path: impl Into<PathBuf>, error: ImageError) -> Self {
+        match error {
+            ImageError::Unsupported(e) => ClimpError::UnsupportedFormat(e.to_string()),
+            e => ClimpError::Decode(path.into(), e),
+        }
+    }
+
+    /// Wraps encoding error of the file at `path`
+    ///
+    /// `ImageError::IoError` is taken for a failed write, so encoders writing into memory
+    /// wrap their errors with `ClimpError::Encode` directly
+    pub fn encode(path: impl Into<PathBuf>, error: ImageError) -> Self {
+        match error {
+            ImageError::Unsupported(e) => ClimpError::UnsupportedFormat(e.to_string()),
+            ImageError::IoError(e) => ClimpError::Io(path.into(), e),
+            e => ClimpError::Encode(path.into(), e),
+        }
+    }
+
+    /// Process exit code for the error
+    ///
+    /// Invalid arguments share code 2 with the argument parser errors
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            ClimpError::Batch { .. } => 1,
+            ClimpError::InvalidArgument(_) => 2,
+            ClimpError::Io(..) => 3,
+            ClimpError::Decode(..) => 4,
+            ClimpError::Encode(..) => 5,
+            ClimpError::UnsupportedFormat(_) => 6,
+        }
+    }
+}
+
+impl fmt::Display for ClimpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClimpError::Decode(path, e) => write!(f, "Failed to decode {path:?}: {e}"),
+            ClimpError::Encode(path, e) => write!(f, "Failed to encode {path:?}: {e}"),
+            ClimpError::UnsupportedFormat(e) => write!(f, "Unsupported format: {e}"),
+            ClimpError::InvalidArgument(e) => write!(f, "Invalid argument: {e}"),
+            ClimpError::Io(path, e) => write!(f, "Failed to access {path:?}: {e}"),
+            ClimpError::Batch { failed, total } => write!(f, "{failed} of {total} files failed"),
+        }
+    }
+}
+
+impl Error for ClimpError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ClimpError::Decode(_, e) | ClimpError::Encode(_, e) => Some(e),
+            ClimpError::Io(_, e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,33 @@
+//! Command Line Image Manipulation Processor
+//!
+//! Image operations used by the `climp` binary, usable on their own:
+//!
+//! ```
+//! use climp::{grayscale, rotate, Image, Luminance};
+//!
+//! let img = Image::from_pixel(4, 2, image::Rgba([200, 100, 50, 255]));
+//! let result = grayscale(&rotate(&img), Luminance::default());
+//!
+//! assert_eq!(result.dimensions(), (2, 4));
+//! ```
+pub mod any_image;
+pub mod ascii;
+pub mod color;
+pub mod error;
+pub mod luminance;
+pub mod lut;
+pub mod methods;
+pub mod palette;
+
+pub use crate::any_image::AnyImage;
+pub use crate::color::parse_color;
+pub use crate::error::ClimpError;
+pub use crate::luminance::Luminance;
+pub use crate::methods::{
+    adjust, apply_lut, aspect_region, blur, clahe, crop, curse, dither, dominant_colors, equalize,
+    extract_palette, flatten, flip_vertical, grayscale, hsl, mirror, monochrome_ugly, pad,
+    padding_to_aspect, padding_to_size, pixelate, quantize, resize, rotate, rotate_by,
+    rotated_dimensions, scale, scaled_dimensions, tone_map, trim, trim_box, zxc, Adjustments,
+    Buffer, Dither, Gravity, HslAdjustments, Image, Image16, ImageF32, Interpolation, PadFill,
+    ResampleFilter, ScaleMode, Threshold, ToneMap,
+};
@@ -0,0 +1,75 @@
+use std::str::FromStr;
+
+/// How a color is turned into a single brightness value
+///
+/// Shared by `grayscale`, `monochrome_ugly`, the histogram equalization and the ASCII renderer,
+/// so every one of them sees the same brightness
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Luminance {
+    /// Plain `(r + g + b) / 3`
+    Average,
+    /// Rec. 601 luma of SD video and JPEG
+    Rec601,
+    /// Rec. 709 luma, which shares its primaries with sRGB
+    #[default]
+    Rec709,
+    /// HSL lightness, the mean of the brightest and the darkest channel
+    Lightness,
+    Red,
+    Green,
+    Blue,
+    /// Perceptual L* of CIELAB, computed from linearized sRGB
+    Lab,
+}
+
+impl Luminance {
+    /// Brightness of an RGB color with channels in 0..1 range, HDR values above 1 give
+    /// brightness above 1
+    pub fn of(self, [r, g, b]: [f32; 3]) -> f32 {
+        match self {
+            Luminance::Average => (r + g + b) / 3.0,
+            Luminance::Rec601 => 0.299 * r + 0.587 * g + 0.114 * b,
+            Luminance::Rec709 => 0.2126 * r + 0.7152 * g + 0.0722 * b,
+            Luminance::Lightness => (r.max(g).max(b) + r.min(g).min(b)) / 2.0,
+            Luminance::Red => r,
+            Luminance::Green => g,
+            Luminance::Blue => b,
+            Luminance::Lab => {
+                let y = 0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b);
+                let f = if y > 216.0 / 24389.0 {
+                    y.cbrt()
+                } else {
+                    (24389.0 / 27.0 * y + 16.0) / 116.0
+                };
+                (116.0 * f - 16.0) / 100.0
+            }
+        }
+    }
+}
+
+impl FromStr for Luminance {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "average" => Ok(Luminance::Average),
+            "rec601" => Ok(Luminance::Rec601),
+            "rec709" => Ok(Luminance::Rec709),
+            "lightness" => Ok(Luminance::Lightness),
+            "red" => Ok(Luminance::Red),
+            "green" => Ok(Luminance::Green),
+            "blue" => Ok(Luminance::Blue),
+            "lab" => Ok(Luminance::Lab),
+            _ => Err(format!("unknown luminance `{s}`")),
+        }
+    }
+}
+
+/// Removes sRGB gamma from the channel
+fn linear(channel: f32) -> f32 {
+    if channel <= 0.04045 {
+        channel / 12.92
+    } else {
+        ((channel + 0.055) / 1.055).powf(2.4)
+    }
+}
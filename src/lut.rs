@@ -0,0 +1,227 @@
+use std::str::FromStr;
+
+/// How a 3D LUT is sampled between its grid points
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LutInterpolation {
+    /// Blends the 8 corners of the grid cell, the common choice
+    #[default]
+    Trilinear,
+    /// Blends the 4 corners of the tetrahedron the color falls in, keeps grays neutral
+    /// and is closer to what Resolve renders
+    Tetrahedral,
+}
+
+impl FromStr for LutInterpolation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "trilinear" => Ok(LutInterpolation::Trilinear),
+            "tetrahedral" => Ok(LutInterpolation::Tetrahedral),
+            _ => Err(format!("unknown LUT interpolation `{s}`")),
+        }
+    }
+}
+
+/// Color lookup table of an Adobe or Resolve `.cube` file
+///
+/// Holds a 1D table, a 3D table or a 1D shaper followed by a 3D table
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lut {
+    /// `TITLE` of the file, if it has one
+    pub title: Option<String>,
+    shaper: Option<Table>,
+    cube: Option<Table>,
+}
+
+/// 1D or 3D table, 3D values go with red changing fastest, then green, then blue
+#[derive(Debug, Clone, PartialEq)]
+struct Table {
+    size: usize,
+    /// Inputs mapped onto the first and the last grid point, per channel
+    domain: ([f32; 3], [f32; 3]),
+    values: Vec<[f32; 3]>,
+}
+
+impl Table {
+    /// Position of the channel value on the grid, clamped into it
+    fn position(&self, value: f32, channel: usize) -> f32 {
+        let (min, max) = (self.domain.0[channel], self.domain.1[channel]);
+        ((value - min) / (max - min)).clamp(0.0, 1.0) * (self.size - 1) as f32
+    }
+
+    /// Maps every channel through its own curve
+    fn curve(&self, rgb: [f32; 3]) -> [f32; 3] {
+        [0, 1, 2].map(|c| {
+            let x = self.position(rgb[c], c);
+            let i = (x as usize).min(self.size - 2);
+            let f = x - i as f32;
+            self.values[i][c] + (self.values[i + 1][c] - self.values[i][c]) * f
+        })
+    }
+
+    /// Looks the color up in the cube
+    fn cube(&self, rgb: [f32; 3], interpolation: LutInterpolation) -> [f32; 3] {
+        let n = self.size;
+        let x = [0, 1, 2].map(|c| self.position(rgb[c], c));
+        let i = x.map(|x| (x as usize).min(n - 2));
+        let [fr, fg, fb] = [0, 1, 2].map(|c| x[c] - i[c] as f32);
+        let at = |r: usize, g: usize, b: usize| {
+            self.values[(i[0] + r) + (i[1] + g) * n + (i[2] + b) * n * n]
+        };
+        let mix = |weights: [(f32, [f32; 3]); 4]| {
+            [0, 1, 2].map(|c| weights.iter().map(|(w, v)| w * v[c]).sum::<f32>())
+        };
+
+        match interpolation {
+            LutInterpolation::Trilinear => {
+                let lerp =
+                    |a: [f32; 3], b: [f32; 3], t: f32| [0, 1, 2].map(|c| a[c] + (b[c] - a[c]) * t);
+                let c00 = lerp(at(0, 0, 0), at(1, 0, 0), fr);
+                let c10 = lerp(at(0, 1, 0), at(1, 1, 0), fr);
+                let c01 = lerp(at(0, 0, 1), at(1, 0, 1), fr);
+                let c11 = lerp(at(0, 1, 1), at(1, 1, 1), fr);
+                lerp(lerp(c00, c10, fg), lerp(c01, c11, fg), fb)
+            }
+            LutInterpolation::Tetrahedral => {
+                let (c000, c111) = (at(0, 0, 0), at(1, 1, 1));
+                // corners of the tetrahedron picked by the order of the fractions
+                let (first, second, [f1, f2, f3]) = if fr > fg {
+                    if fg > fb {
+                        (at(1, 0, 0), at(1, 1, 0), [fr, fg, fb])
+                    } else if fr > fb {
+                        (at(1, 0, 0), at(1, 0, 1), [fr, fb, fg])
+                    } else {
+                        (at(0, 0, 1), at(1, 0, 1), [fb, fr, fg])
+                    }
+                } else if fb > fg {
+                    (at(0, 0, 1), at(0, 1, 1), [fb, fg, fr])
+                } else if fb > fr {
+                    (at(0, 1, 0), at(0, 1, 1), [fg, fb, fr])
+                } else {
+                    (at(0, 1, 0), at(1, 1, 0), [fg, fr, fb])
+                };
+                mix([
+                    (1.0 - f1, c000),
+                    (f1 - f2, first),
+                    (f2 - f3, second),
+                    (f3, c111),
+                ])
+            }
+        }
+    }
+}
+
+impl Lut {
+    /// Maps the color with channels in 0..1 range, inputs outside of the domain are clamped into it
+    pub fn apply(&self, rgb: [f32; 3], interpolation: LutInterpolation) -> [f32; 3] {
+        let rgb = match &self.shaper {
+            Some(shaper) => shaper.curve(rgb),
+            None => rgb,
+        };
+        match &self.cube {
+            Some(cube) => cube.cube(rgb, interpolation),
+            None => rgb,
+        }
+    }
+}
+
+/// Parses a `.cube` file
+///
+/// Takes `LUT_1D_SIZE` and `LUT_3D_SIZE` tables with `DOMAIN_MIN` and `DOMAIN_MAX` of Adobe
+/// or `LUT_1D_INPUT_RANGE` and `LUT_3D_INPUT_RANGE` of Resolve. A file with both sizes
+/// holds a 1D shaper followed by the 3D table
+pub fn parse_cube(text: &str) -> Result<Lut, String> {
+    let mut title = None;
+    let (mut size_1d, mut size_3d) = (None, None);
+    let mut domain = ([0.0; 3], [1.0; 3]);
+    let (mut range_1d, mut range_3d) = (None, None);
+    let mut values = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        let error = |message: String| format!("line {}: {message}", number + 1);
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (keyword, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        match keyword {
+            "TITLE" => title = Some(rest.trim_matches('"').to_string()),
+            "LUT_1D_SIZE" => size_1d = Some(parse_size(rest, 2..=65536).map_err(error)?),
+            "LUT_3D_SIZE" => size_3d = Some(parse_size(rest, 2..=256).map_err(error)?),
+            "DOMAIN_MIN" => domain.0 = parse_triplet(rest).map_err(error)?,
+            "DOMAIN_MAX" => domain.1 = parse_triplet(rest).map_err(error)?,
+            "LUT_1D_INPUT_RANGE" => range_1d = Some(parse_range(rest).map_err(error)?),
+            "LUT_3D_INPUT_RANGE" => range_3d = Some(parse_range(rest).map_err(error)?),
+            _ if keyword.starts_with(|c: char| c.is_ascii_alphabetic()) => {
+                // other keywords, e.g. LUT_IN_VIDEO_RANGE of Resolve, don't change the table
+            }
+            _ => values.push(parse_triplet(line).map_err(error)?),
+        }
+    }
+
+    if (0..3).any(|c| domain.1[c] <= domain.0[c]) {
+        return Err("DOMAIN_MAX has to be above DOMAIN_MIN".to_string());
+    }
+    let expected = size_1d.unwrap_or(0) + size_3d.map_or(0, |n| n * n * n);
+    if expected == 0 {
+        return Err("neither LUT_1D_SIZE nor LUT_3D_SIZE is given".to_string());
+    }
+    if values.len() != expected {
+        return Err(format!(
+            "expected {expected} table entries, found {}",
+            values.len()
+        ));
+    }
+
+    let cube = size_3d.map(|size| Table {
+        size,
+        domain: range_3d.unwrap_or(domain),
+        values: values.split_off(values.len() - size * size * size),
+    });
+    let shaper = size_1d.map(|size| Table {
+        size,
+        domain: range_1d.unwrap_or(domain),
+        values,
+    });
+    Ok(Lut {
+        title,
+        shaper,
+        cube,
+    })
+}
+
+fn parse_size(value: &str, range: std::ops::RangeInclusive<usize>) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(size) if range.contains(&size) => Ok(size),
+        _ => Err(format!(
+            "`{value}` is not a size from {} to {}",
+            range.start(),
+            range.end()
+        )),
+    }
+}
+
+fn parse_triplet(value: &str) -> Result<[f32; 3], String> {
+    let numbers = value
+        .split_whitespace()
+        .map(str::parse::<f32>)
+        .collect::<Result<Vec<_>, _>>();
+    match numbers.as_deref() {
+        Ok(&[r, g, b]) if [r, g, b].iter().all(|v| v.is_finite()) => Ok([r, g, b]),
+        _ => Err(format!("`{value}` is not three numbers")),
+    }
+}
+
+/// Parses `min max` input range of Resolve, shared by the channels
+fn parse_range(value: &str) -> Result<([f32; 3], [f32; 3]), String> {
+    let numbers = value
+        .split_whitespace()
+        .map(str::parse::<f32>)
+        .collect::<Result<Vec<_>, _>>();
+    match numbers.as_deref() {
+        Ok(&[min, max]) if min < max => Ok(([min; 3], [max; 3])),
+        _ => Err(format!("`{value}` is not a range like 0.0 1.0")),
+    }
+}
@@ -1,12 +1,14 @@
-mod ascii;
 mod commands;
 
 use commands::{make_commands, Args};
-use std::error::Error;
-
-fn main() -> Result<(), Box<dyn Error>> {
-
-    let _ = Args::match_command(&mut Args::new(), make_commands());
-
-    Ok(())
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    match Args::match_command(&mut Args::new(), make_commands()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("\x1b[31merror:\x1b[0m {e}");
+            ExitCode::from(e.exit_code())
+        }
+    }
 }
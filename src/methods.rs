@@ -0,0 +1,1585 @@
+use image::imageops::{self, FilterType};
+use image::{ImageBuffer, Pixel, Primitive, Rgb, Rgba};
+
+use crate::error::ClimpError;
+use crate::luminance::Luminance;
+use crate::lut::{Lut, LutInterpolation};
+use crate::palette::{nearest, palette_of, PaletteMethod};
+use num_traits::NumCast;
+use std::str::FromStr;
+
+/// RGBA image with `S` channels, operations work with any of the ones below
+pub type Buffer<S> = ImageBuffer<Rgba<S>, Vec<S>>;
+
+/// 8 bits per channel image
+pub type Image = Buffer<u8>;
+
+/// 16 bits per channel image
+pub type Image16 = Buffer<u16>;
+
+/// 32-bit float per channel image, values above 1 keep HDR highlights
+pub type ImageF32 = Buffer<f32>;
+
+/// Rotates the image by fixed amount of 90 degrees
+pub fn rotate<S>(img: &Buffer<S>) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    imageops::rotate90(img)
+}
+
+/// Mirrors the image horizontally
+pub fn mirror<S>(img: &Buffer<S>) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    imageops::flip_horizontal(img)
+}
+
+/// Flips the image vertically
+pub fn flip_vertical<S>(img: &Buffer<S>) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    imageops::flip_vertical(img)
+}
+
+/// Resizes the image to the given dimensions.
+///
+/// If the image is larger than the `new_dims`,
+/// it will be cropped and missed pixels are left transparent
+pub fn resize<S>(img: &Buffer<S>, new_dims: (u32, u32)) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let (old_width, old_height) = img.dimensions();
+    let (new_width, new_height) = new_dims;
+
+    let mut resized = ImageBuffer::new(new_width, new_height);
+
+    for (new_x, new_y, pixel) in resized.enumerate_pixels_mut() {
+        let old_x = (new_x as f32 * (old_width as f32 / new_width as f32)) as u32;
+        let old_y = (new_y as f32 * (old_height as f32 / new_height as f32)) as u32;
+
+        if let Some(old_pixel) = img.get_pixel_checked(old_x, old_y) {
+            *pixel = *old_pixel;
+        }
+    }
+
+    resized
+}
+
+/// How `scale` treats the requested width and height
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScaleMode {
+    /// Keeps aspect ratio, the image fits inside of the box
+    #[default]
+    Fit,
+    /// Keeps aspect ratio, the image covers the box and the overflow is cropped around the center
+    Fill,
+    /// Stretches the image to the box
+    Exact,
+}
+
+impl FromStr for ScaleMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fit" => Ok(ScaleMode::Fit),
+            "fill" => Ok(ScaleMode::Fill),
+            "exact" => Ok(ScaleMode::Exact),
+            _ => Err(format!("unknown scale mode `{s}`")),
+        }
+    }
+}
+
+/// Resampling filter of `scale`, named after the ones of `imageops`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResampleFilter {
+    Nearest,
+    Triangle,
+    CatmullRom,
+    Gaussian,
+    #[default]
+    Lanczos3,
+}
+
+impl FromStr for ResampleFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nearest" => Ok(ResampleFilter::Nearest),
+            "triangle" => Ok(ResampleFilter::Triangle),
+            "catmull-rom" => Ok(ResampleFilter::CatmullRom),
+            "gaussian" => Ok(ResampleFilter::Gaussian),
+            "lanczos3" => Ok(ResampleFilter::Lanczos3),
+            _ => Err(format!("unknown resampling filter `{s}`")),
+        }
+    }
+}
+
+impl From<ResampleFilter> for FilterType {
+    fn from(filter: ResampleFilter) -> Self {
+        match filter {
+            ResampleFilter::Nearest => FilterType::Nearest,
+            ResampleFilter::Triangle => FilterType::Triangle,
+            ResampleFilter::CatmullRom => FilterType::CatmullRom,
+            ResampleFilter::Gaussian => FilterType::Gaussian,
+            ResampleFilter::Lanczos3 => FilterType::Lanczos3,
+        }
+    }
+}
+
+/// Computes size of the scaled image
+///
+/// A missing side follows the aspect ratio of the other one, `Fill` returns the size before cropping
+pub fn scaled_dimensions(
+    (width, height): (u32, u32),
+    (new_width, new_height): (Option<u32>, Option<u32>),
+    mode: ScaleMode,
+) -> (u32, u32) {
+    let (w, h) = (width as f64, height as f64);
+    let (new_w, new_h) = match (new_width, new_height) {
+        (Some(new_w), Some(new_h)) => (new_w as f64, new_h as f64),
+        (Some(new_w), None) => (new_w as f64, h * new_w as f64 / w),
+        (None, Some(new_h)) => (w * new_h as f64 / h, new_h as f64),
+        (None, None) => (w, h),
+    };
+
+    let ratio = match mode {
+        ScaleMode::Fit => (new_w / w).min(new_h / h),
+        ScaleMode::Fill => (new_w / w).max(new_h / h),
+        ScaleMode::Exact => return (new_w.round().max(1.0) as u32, new_h.round().max(1.0) as u32),
+    };
+    (
+        (w * ratio).round().max(1.0) as u32,
+        (h * ratio).round().max(1.0) as u32,
+    )
+}
+
+/// Scales the image to `new_dims` resampling it with the `filter`
+///
+/// Either side can be omitted to keep the aspect ratio,
+/// `mode` decides what happens when both are given
+pub fn scale<S>(
+    img: &Buffer<S>,
+    new_dims: (Option<u32>, Option<u32>),
+    mode: ScaleMode,
+    filter: FilterType,
+) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let (width, height) = scaled_dimensions(img.dimensions(), new_dims, mode);
+    let scaled = unclipped(img, |img| imageops::resize(img, width, height, filter));
+
+    match (mode, new_dims) {
+        (ScaleMode::Fill, (Some(box_width), Some(box_height))) => {
+            let (box_width, box_height) = (box_width.min(width), box_height.min(height));
+            let x = (width - box_width) / 2;
+            let y = (height - box_height) / 2;
+            imageops::crop_imm(&scaled, x, y, box_width, box_height).to_image()
+        }
+        _ => scaled,
+    }
+}
+
+/// Interpolation of the pixels sampled between the source ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    Nearest,
+    #[default]
+    Bilinear,
+    /// Catmull-Rom spline over 4x4 pixels, sharper than bilinear
+    Bicubic,
+}
+
+impl FromStr for Interpolation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nearest" => Ok(Interpolation::Nearest),
+            "bilinear" => Ok(Interpolation::Bilinear),
+            "bicubic" => Ok(Interpolation::Bicubic),
+            _ => Err(format!("unknown interpolation `{s}`")),
+        }
+    }
+}
+
+/// Computes size of the canvas fitting the image rotated clockwise by `degrees`
+pub fn rotated_dimensions((width, height): (u32, u32), degrees: f32) -> (u32, u32) {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let (w, h) = (width as f32, height as f32);
+    // Small epsilon keeps rounding errors of exact angles from adding a pixel
+    (
+        (w * cos.abs() + h * sin.abs() - 1e-3).ceil().max(1.0) as u32,
+        (w * sin.abs() + h * cos.abs() - 1e-3).ceil().max(1.0) as u32,
+    )
+}
+
+/// Rotates the image clockwise by any angle
+///
+/// With `expand` the canvas grows to fit the whole rotated image,
+/// otherwise it keeps the original size and the corners are cropped.
+/// Uncovered areas are filled with the `background`.
+///
+/// Multiples of 90 degrees are turned without resampling when the canvas allows it
+pub fn rotate_by<S>(
+    img: &Buffer<S>,
+    degrees: f32,
+    interpolation: Interpolation,
+    expand: bool,
+    background: Rgba<u8>,
+) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let degrees = degrees.rem_euclid(360.0);
+    let (width, height) = img.dimensions();
+    if expand || width == height {
+        if degrees == 0.0 {
+            return img.clone();
+        } else if degrees == 90.0 {
+            return imageops::rotate90(img);
+        } else if degrees == 180.0 {
+            return imageops::rotate180(img);
+        } else if degrees == 270.0 {
+            return imageops::rotate270(img);
+        }
+    }
+
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let (w, h) = (width as f32, height as f32);
+    let (new_width, new_height) = if expand {
+        rotated_dimensions((width, height), degrees)
+    } else {
+        (width, height)
+    };
+
+    let background = convert_color::<S>(background);
+    let (center_x, center_y) = (w / 2.0, h / 2.0);
+    let (new_center_x, new_center_y) = (new_width as f32 / 2.0, new_height as f32 / 2.0);
+
+    let mut rotated = Buffer::new(new_width, new_height);
+    for (x, y, pixel) in rotated.enumerate_pixels_mut() {
+        let dx = x as f32 + 0.5 - new_center_x;
+        let dy = y as f32 + 0.5 - new_center_y;
+        // Source pixel is found by rotating the destination one back
+        let src_x = cos * dx + sin * dy + center_x - 0.5;
+        let src_y = -sin * dx + cos * dy + center_y - 0.5;
+        *pixel = sample(img, src_x, src_y, interpolation, background);
+    }
+    rotated
+}
+
+/// Samples the image at pixel center coordinates, pixels outside of it are `background`
+///
+/// Colors are weighted by alpha, so transparent neighbours don't darken the edges
+fn sample<S>(
+    img: &Buffer<S>,
+    x: f32,
+    y: f32,
+    interpolation: Interpolation,
+    background: Rgba<S>,
+) -> Rgba<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let pixel_at = |px: i64, py: i64| {
+        if px < 0 || py < 0 || px >= img.width() as i64 || py >= img.height() as i64 {
+            background
+        } else {
+            *img.get_pixel(px as u32, py as u32)
+        }
+    };
+
+    let (taps, weights): (i64, Box<dyn Fn(f32) -> f32>) = match interpolation {
+        Interpolation::Nearest => return pixel_at(x.round() as i64, y.round() as i64),
+        Interpolation::Bilinear => (2, Box::new(|t: f32| 1.0 - t.abs())),
+        Interpolation::Bicubic => (4, Box::new(catmull_rom)),
+    };
+
+    let (x0, y0) = (
+        x.floor() as i64 - (taps / 2 - 1),
+        y.floor() as i64 - (taps / 2 - 1),
+    );
+    let mut sum = [0.0f32; 4];
+    for j in 0..taps {
+        let wy = weights(y - (y0 + j) as f32);
+        for i in 0..taps {
+            let weight = weights(x - (x0 + i) as f32) * wy;
+            let pixel = pixel_at(x0 + i, y0 + j);
+            let alpha = to_f32(pixel[3]);
+            for c in 0..3 {
+                sum[c] += to_f32(pixel[c]) * alpha * weight;
+            }
+            sum[3] += alpha * weight;
+        }
+    }
+
+    let alpha = sum[3].clamp(0.0, max::<S>());
+    if alpha <= 0.0 {
+        return Rgba([S::DEFAULT_MIN_VALUE; 4]);
+    }
+    Rgba([
+        from_f32(sum[0] / sum[3]),
+        from_f32(sum[1] / sum[3]),
+        from_f32(sum[2] / sum[3]),
+        from_f32(alpha),
+    ])
+}
+
+/// Cubic convolution kernel with `a = -0.5`
+fn catmull_rom(t: f32) -> f32 {
+    let t = t.abs();
+    if t < 1.0 {
+        1.5 * t * t * t - 2.5 * t * t + 1.0
+    } else if t < 2.0 {
+        -0.5 * t * t * t + 2.5 * t * t - 4.0 * t + 2.0
+    } else {
+        0.0
+    }
+}
+
+/// Side or corner of the image a crop is anchored to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Gravity {
+    #[default]
+    Center,
+    North,
+    South,
+    East,
+    West,
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
+}
+
+impl Gravity {
+    /// Offset of `size` long span inside of `total` long one along x and y axes
+    fn offset(self, (total_w, total_h): (u32, u32), (w, h): (u32, u32)) -> (u32, u32) {
+        let (free_w, free_h) = (total_w - w, total_h - h);
+        let x = match self {
+            Gravity::West | Gravity::NorthWest | Gravity::SouthWest => 0,
+            Gravity::East | Gravity::NorthEast | Gravity::SouthEast => free_w,
+            _ => free_w / 2,
+        };
+        let y = match self {
+            Gravity::North | Gravity::NorthEast | Gravity::NorthWest => 0,
+            Gravity::South | Gravity::SouthEast | Gravity::SouthWest => free_h,
+            _ => free_h / 2,
+        };
+        (x, y)
+    }
+}
+
+impl FromStr for Gravity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "center" => Ok(Gravity::Center),
+            "north" => Ok(Gravity::North),
+            "south" => Ok(Gravity::South),
+            "east" => Ok(Gravity::East),
+            "west" => Ok(Gravity::West),
+            "northeast" => Ok(Gravity::NorthEast),
+            "northwest" => Ok(Gravity::NorthWest),
+            "southeast" => Ok(Gravity::SouthEast),
+            "southwest" => Ok(Gravity::SouthWest),
+            _ => Err(format!("unknown gravity `{s}`")),
+        }
+    }
+}
+
+/// Crops `width` x `height` region with top left corner at `x`, `y`
+///
+/// Returns an error if the region is empty or falls outside of the image
+pub fn crop<S>(
+    img: &Buffer<S>,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> Result<Buffer<S>, ClimpError>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let (img_width, img_height) = img.dimensions();
+    if width == 0 || height == 0 {
+        return Err(ClimpError::InvalidArgument(format!(
+            "crop region {width}x{height} is empty"
+        )));
+    }
+    if x as u64 + width as u64 > img_width as u64 || y as u64 + height as u64 > img_height as u64 {
+        return Err(ClimpError::InvalidArgument(format!(
+            "crop region {width}x{height} at {x},{y} falls outside of the {img_width}x{img_height} image"
+        )));
+    }
+    Ok(imageops::crop_imm(img, x, y, width, height).to_image())
+}
+
+/// Finds the largest region with `aspect` ratio of width to height, placed according to `gravity`
+///
+/// Returns `x`, `y`, `width` and `height` of the region
+pub fn aspect_region(dims: (u32, u32), aspect: f64, gravity: Gravity) -> (u32, u32, u32, u32) {
+    let (width, height) = dims;
+    let size = if width as f64 / height as f64 > aspect {
+        (
+            ((height as f64 * aspect).round() as u32).clamp(1, width),
+            height,
+        )
+    } else {
+        (
+            width,
+            ((width as f64 / aspect).round() as u32).clamp(1, height),
+        )
+    };
+    let (x, y) = gravity.offset(dims, size);
+    (x, y, size.0, size.1)
+}
+
+/// Finds the box of the image left after trimming its border
+///
+/// Border color is taken from the top left corner, pixels which differ from it
+/// by no more than `tolerance` in every channel (in 0..255 range) belong to the border.
+/// Transparent border is matched by alpha only, whatever color is stored under it.
+///
+/// Returns `x`, `y`, `width` and `height` of the box, `None` if the whole image is a border
+pub fn trim_box<S>(img: &Buffer<S>, tolerance: f32) -> Option<(u32, u32, u32, u32)>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let corner = img.get_pixel_checked(0, 0)?.0.map(to_u8_scale);
+    let is_border = |pixel: &Rgba<S>| {
+        let pixel = pixel.0.map(to_u8_scale);
+        if corner[3] <= tolerance {
+            pixel[3] <= tolerance
+        } else {
+            pixel
+                .iter()
+                .zip(corner)
+                .all(|(channel, border)| (channel - border).abs() <= tolerance)
+        }
+    };
+
+    let (mut left, mut top, mut right, mut bottom) = (u32::MAX, u32::MAX, 0, 0);
+    for (x, y, pixel) in img.enumerate_pixels() {
+        if !is_border(pixel) {
+            left = left.min(x);
+            top = top.min(y);
+            right = right.max(x);
+            bottom = bottom.max(y);
+        }
+    }
+
+    (left <= right).then(|| (left, top, right - left + 1, bottom - top + 1))
+}
+
+/// Trims the border of near-constant color, see `trim_box`
+///
+/// Image which is a border as a whole is returned as it is
+pub fn trim<S>(img: &Buffer<S>, tolerance: f32) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    match trim_box(img, tolerance) {
+        Some((x, y, width, height)) => imageops::crop_imm(img, x, y, width, height).to_image(),
+        None => img.clone(),
+    }
+}
+
+/// What fills the area added by `pad`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PadFill {
+    /// Solid color, transparent one included
+    Color(Rgba<u8>),
+    /// Edge pixels of the image stretched outwards
+    Edge,
+    /// Copy of the image scaled to cover the canvas and blurred with the radius
+    Blur(u32),
+}
+
+/// Paddings to add on `(left, top, right, bottom)` sides for the image to get `size`
+///
+/// The image is placed according to `gravity`, sides smaller than the image are kept
+pub fn padding_to_size(
+    dims: (u32, u32),
+    size: (u32, u32),
+    gravity: Gravity,
+) -> (u32, u32, u32, u32) {
+    let canvas = (size.0.max(dims.0), size.1.max(dims.1));
+    let (left, top) = gravity.offset(canvas, dims);
+    (left, top, canvas.0 - dims.0 - left, canvas.1 - dims.1 - top)
+}
+
+/// Paddings to add for the image to get `aspect` ratio of width to height, see `padding_to_size`
+pub fn padding_to_aspect(dims: (u32, u32), aspect: f64, gravity: Gravity) -> (u32, u32, u32, u32) {
+    let (width, height) = dims;
+    let size = if width as f64 / height as f64 > aspect {
+        (width, (width as f64 / aspect).round() as u32)
+    } else {
+        ((height as f64 * aspect).round() as u32, height)
+    };
+    padding_to_size(dims, size, gravity)
+}
+
+/// Extends the canvas by `(left, top, right, bottom)` pixels and fills the new area
+pub fn pad<S>(img: &Buffer<S>, padding: (u32, u32, u32, u32), fill: PadFill) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let (left, top, right, bottom) = padding;
+    let (width, height) = img.dimensions();
+    let (canvas_width, canvas_height) = (left + width + right, top + height + bottom);
+
+    let mut canvas = match fill {
+        PadFill::Color(color) => {
+            Buffer::from_pixel(canvas_width, canvas_height, convert_color(color))
+        }
+        PadFill::Edge => Buffer::from_fn(canvas_width, canvas_height, |x, y| {
+            let x = x.saturating_sub(left).min(width - 1);
+            let y = y.saturating_sub(top).min(height - 1);
+            *img.get_pixel(x, y)
+        }),
+        PadFill::Blur(radius) => {
+            let size = (Some(canvas_width), Some(canvas_height));
+            let cover = scale(img, size, ScaleMode::Fill, FilterType::Triangle);
+            blur(&cover, radius)
+        }
+    };
+    imageops::replace(&mut canvas, img, left as i64, top as i64);
+    canvas
+}
+
+/// Tonal adjustments applied by `adjust`, the default changes nothing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Adjustments {
+    /// Offset from -100 to 100 percent of the full range
+    pub brightness: f32,
+    /// Percent of contrast change around the middle gray, -100 makes the image flat
+    pub contrast: f32,
+    /// Gamma correction, must be positive, values above 1 brighten midtones
+    pub gamma: f32,
+    /// Exposure change in stops, every stop doubles the light
+    pub exposure: f32,
+}
+
+impl Default for Adjustments {
+    fn default() -> Self {
+        Self {
+            brightness: 0.0,
+            contrast: 0.0,
+            gamma: 1.0,
+            exposure: 0.0,
+        }
+    }
+}
+
+impl Adjustments {
+    /// Maps a channel value in 0..1 range, exposure goes first as it works on linear light
+    pub fn apply(&self, value: f32) -> f32 {
+        let value = value * 2f32.powf(self.exposure) + self.brightness / 100.0;
+        let value = (value - 0.5) * (1.0 + self.contrast / 100.0) + 0.5;
+        value.max(0.0).powf(1.0 / self.gamma)
+    }
+}
+
+/// Adjusts brightness, contrast, gamma and exposure of the color channels
+///
+/// Integer channels are mapped through a lookup table computed once for every possible value,
+/// floats are mapped directly so HDR values above 1 are kept
+pub fn adjust<S>(img: &Buffer<S>, adjustments: &Adjustments) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let max = max::<S>();
+    let lut = (max > 1.0).then(|| {
+        (0..=max as u32)
+            .map(|v| from_f32((adjustments.apply(v as f32 / max) * max).min(max)))
+            .collect::<Vec<S>>()
+    });
+
+    let mut adjusted = img.clone();
+    for pixel in adjusted.pixels_mut() {
+        for channel in pixel.0.iter_mut().take(3) {
+            *channel = match &lut {
+                Some(lut) => lut[to_f32(*channel) as usize],
+                None => from_f32(adjustments.apply(to_f32(*channel))),
+            };
+        }
+    }
+    adjusted
+}
+
+/// Hue, saturation and lightness changes applied by `hsl`, the default changes nothing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HslAdjustments {
+    /// Hue rotation in degrees
+    pub hue: f32,
+    /// Saturation factor, 0 makes the image gray
+    pub saturation: f32,
+    /// Lightness factor
+    pub lightness: f32,
+    /// Hues from the first to the second one in degrees going up, which are changed.
+    /// All of them if it's `None`, gray pixels have no hue and are left out of any range
+    pub hue_range: Option<(f32, f32)>,
+    /// Degrees beyond the `hue_range` over which the change fades out
+    pub feather: f32,
+}
+
+impl Default for HslAdjustments {
+    fn default() -> Self {
+        Self {
+            hue: 0.0,
+            saturation: 1.0,
+            lightness: 1.0,
+            hue_range: None,
+            feather: 15.0,
+        }
+    }
+}
+
+impl HslAdjustments {
+    /// How much of the change applies to the hue, from 0 to 1
+    fn weight(&self, hue: f32, saturation: f32) -> f32 {
+        let Some((from, to)) = self.hue_range else {
+            return 1.0;
+        };
+        if saturation == 0.0 {
+            return 0.0;
+        }
+        let span = if to - from >= 360.0 {
+            360.0
+        } else {
+            (to - from).rem_euclid(360.0)
+        };
+        if (hue - from).rem_euclid(360.0) <= span {
+            return 1.0;
+        }
+        let distance = (from - hue)
+            .rem_euclid(360.0)
+            .min((hue - to).rem_euclid(360.0));
+        if self.feather > 0.0 {
+            (1.0 - distance / self.feather).max(0.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Rotates hue and scales saturation and lightness, optionally only of a range of hues
+///
+/// HDR pixels brighter than white are converted relative to their brightest channel,
+/// alpha channel is untouched
+pub fn hsl<S>(img: &Buffer<S>, adjustments: &HslAdjustments) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let max = max::<S>();
+    let mut adjusted = img.clone();
+    for pixel in adjusted.pixels_mut() {
+        let rgb = [0, 1, 2].map(|i| to_f32(pixel[i]) / max);
+        let peak = rgb.iter().copied().fold(1.0, f32::max);
+        let (h, s, l) = rgb_to_hsl(rgb.map(|c| c / peak));
+
+        let weight = adjustments.weight(h, s);
+        if weight == 0.0 {
+            continue;
+        }
+        let new = hsl_to_rgb(
+            h + adjustments.hue,
+            (s * adjustments.saturation).clamp(0.0, 1.0),
+            (l * adjustments.lightness).clamp(0.0, 1.0),
+        );
+        for (i, channel) in pixel.0.iter_mut().take(3).enumerate() {
+            let value = rgb[i] + (new[i] * peak - rgb[i]) * weight;
+            *channel = from_f32(value * max);
+        }
+    }
+    adjusted
+}
+
+/// Converts RGB in 0..1 range into hue in degrees, saturation and lightness
+fn rgb_to_hsl([r, g, b]: [f32; 3]) -> (f32, f32, f32) {
+    let (high, low) = (r.max(g).max(b), r.min(g).min(b));
+    let lightness = (high + low) / 2.0;
+    let delta = high - low;
+    if delta <= f32::EPSILON {
+        return (0.0, 0.0, lightness);
+    }
+
+    let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+    let hue = if high == r {
+        (g - b) / delta
+    } else if high == g {
+        (b - r) / delta + 2.0
+    } else {
+        (r - g) / delta + 4.0
+    };
+    (
+        (hue * 60.0).rem_euclid(360.0),
+        saturation.min(1.0),
+        lightness,
+    )
+}
+
+/// Converts hue in degrees, saturation and lightness into RGB in 0..1 range
+fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> [f32; 3] {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let low = lightness - chroma / 2.0;
+    [r + low, g + low, b + low]
+}
+
+/// Grades colors by the lookup table, `strength` from 0 to 1 blends the result with the original
+///
+/// Channels are looked up in 0..1 range, so HDR values above 1 are clamped into the domain
+/// of the table. Alpha channel is untouched
+pub fn apply_lut<S>(
+    img: &Buffer<S>,
+    lut: &Lut,
+    interpolation: LutInterpolation,
+    strength: f32,
+) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let max = max::<S>();
+    let mut graded = img.clone();
+    for pixel in graded.pixels_mut() {
+        let rgb = [0, 1, 2].map(|i| to_f32(pixel[i]) / max);
+        let new = lut.apply(rgb, interpolation);
+        for (i, channel) in pixel.0.iter_mut().take(3).enumerate() {
+            *channel = from_f32((rgb[i] + (new[i] - rgb[i]) * strength) * max);
+        }
+    }
+    graded
+}
+
+/// Spreads luminance of the image evenly over the whole range via its histogram
+///
+/// Colors are shifted by the luminance change, so hues are kept, alpha channel is untouched.
+/// HDR images come out in 0..1 range
+pub fn equalize<S>(img: &Buffer<S>) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let (bins, count) = luma_bins(img);
+    let mut histogram = vec![0.0; count];
+    for &bin in &bins {
+        histogram[bin] += 1.0;
+    }
+    let lut = equalization_lut(histogram);
+
+    let width = img.width();
+    relight(img, |x, y| lut[bins[(y * width + x) as usize]])
+}
+
+/// Contrast limited adaptive histogram equalization (CLAHE)
+///
+/// Every `tile_size` square gets its own equalization, blended bilinearly between the tiles.
+/// Histogram bins are clipped at `clip_limit` times their average height to keep noise of flat
+/// areas down, 1 leaves the image nearly as it is
+pub fn clahe<S>(img: &Buffer<S>, tile_size: u32, clip_limit: f32) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let (width, height) = img.dimensions();
+    let tile_size = tile_size.max(1);
+    let (tiles_x, tiles_y) = (width.div_ceil(tile_size), height.div_ceil(tile_size));
+    let (bins, count) = luma_bins(img);
+
+    let mut histograms = vec![vec![0.0; count]; (tiles_x * tiles_y) as usize];
+    for (i, &bin) in bins.iter().enumerate() {
+        let (x, y) = (i as u32 % width, i as u32 / width);
+        histograms[((y / tile_size) * tiles_x + x / tile_size) as usize][bin] += 1.0;
+    }
+    let luts = histograms
+        .into_iter()
+        .map(|mut histogram| {
+            let total: f32 = histogram.iter().sum();
+            let limit = (clip_limit * total / count as f32).max(1.0);
+            let excess: f32 = histogram.iter().map(|h| (h - limit).max(0.0)).sum();
+            for h in histogram.iter_mut() {
+                *h = h.min(limit) + excess / count as f32;
+            }
+            equalization_lut(histogram)
+        })
+        .collect::<Vec<_>>();
+
+    // position between the centers of the neighbouring tiles along one axis
+    let neighbours = |position: u32, tiles: u32| {
+        let t = ((position as f32 + 0.5) / tile_size as f32 - 0.5).max(0.0);
+        let first = (t as u32).min(tiles - 1);
+        (
+            first,
+            (first + 1).min(tiles - 1),
+            (t - first as f32).min(1.0),
+        )
+    };
+    relight(img, |x, y| {
+        let bin = bins[(y * width + x) as usize];
+        let (x0, x1, wx) = neighbours(x, tiles_x);
+        let (y0, y1, wy) = neighbours(y, tiles_y);
+        let lut = |tx: u32, ty: u32| luts[(ty * tiles_x + tx) as usize][bin];
+        let top = lut(x0, y0) * (1.0 - wx) + lut(x1, y0) * wx;
+        let bottom = lut(x0, y1) * (1.0 - wx) + lut(x1, y1) * wx;
+        top * (1.0 - wy) + bottom * wy
+    })
+}
+
+/// Luminance of every pixel as a histogram bin and the number of bins
+///
+/// 8-bit images get a bin for every value, deeper ones are binned into 4096 levels
+/// from black to the brightest pixel or white, whichever is brighter
+fn luma_bins<S>(img: &Buffer<S>) -> (Vec<usize>, usize)
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let count = if max::<S>() == 255.0 { 256 } else { 4096 };
+    let lumas = img.pixels().map(luma).collect::<Vec<_>>();
+    let peak = lumas.iter().copied().fold(max::<S>(), f32::max);
+    let bins = lumas
+        .iter()
+        .map(|l| ((l / peak * (count - 1) as f32).round() as usize).min(count - 1))
+        .collect();
+    (bins, count)
+}
+
+/// Maps every histogram bin to its cumulative share in 0..1 range, with the darkest
+/// present bin at 0
+fn equalization_lut(histogram: Vec<f32>) -> Vec<f32> {
+    let mut cdf = histogram;
+    for i in 1..cdf.len() {
+        cdf[i] += cdf[i - 1];
+    }
+    let total = cdf.last().copied().unwrap_or_default();
+    let darkest = cdf.iter().copied().find(|&c| c > 0.0).unwrap_or_default();
+    if total <= darkest {
+        // a single luminance has nowhere to spread
+        let last = (cdf.len() - 1).max(1) as f32;
+        return (0..cdf.len()).map(|i| i as f32 / last).collect();
+    }
+    cdf.iter()
+        .map(|c| ((c - darkest) / (total - darkest)).max(0.0))
+        .collect()
+}
+
+/// Default luminance of the pixel in the scale of its channels
+fn luma<S: Primitive>(pixel: &Rgba<S>) -> f32 {
+    brightness(pixel, Luminance::default()) * max::<S>()
+}
+
+/// Shifts colors of every pixel so its luminance becomes `new_luma(x, y)` in 0..1 range
+fn relight<S, F>(img: &Buffer<S>, new_luma: F) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+    F: Fn(u32, u32) -> f32,
+{
+    let max = max::<S>();
+    let mut relit = img.clone();
+    for (x, y, pixel) in relit.enumerate_pixels_mut() {
+        let shift = new_luma(x, y) * max - luma(pixel);
+        for channel in pixel.0.iter_mut().take(3) {
+            *channel = from_f32((to_f32(*channel) + shift).min(max));
+        }
+    }
+    relit
+}
+
+/// Pixelates the image based via `resize` function
+///
+/// Pixels `resize` misses are left transparent
+pub fn pixelate<S>(img: &Buffer<S>, new_dims: (u32, u32)) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let old_dims = img.dimensions();
+
+    let small = resize(img, ((old_dims.0 / new_dims.0), (old_dims.1 / new_dims.1)));
+
+    resize(&small, old_dims)
+}
+
+/// Blurs the image via standard gaussian blur
+///
+/// It is not recommended to use large values for blur `radius` as the method complexity is not constant
+pub fn blur<S>(img: &Buffer<S>, radius: u32) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    unclipped(img, |img| imageops::blur(img, radius as f32))
+}
+
+/// Luminance threshold of `monochrome_ugly`, levels are in 0..255 range whatever the channel
+/// type is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Threshold {
+    /// The same level for the whole image
+    Fixed(f32),
+    /// Level separating the histogram into two classes best, by Otsu's method
+    Otsu,
+    /// Mean of the `window` square around every pixel less the `offset`
+    Mean { window: u32, offset: f32 },
+    /// Gaussian weighted mean of the `window` square around every pixel less the `offset`
+    Gaussian { window: u32, offset: f32 },
+}
+
+impl Default for Threshold {
+    fn default() -> Self {
+        Threshold::Fixed(128.0)
+    }
+}
+
+/// Monochromes the image into black and white regions based on `luminance`
+///
+/// Pixels at the `threshold` or above it become white, alpha channel is kept.
+/// Local thresholds follow uneven lighting, e.g. of scanned documents
+pub fn monochrome_ugly<S>(img: &Buffer<S>, threshold: Threshold, luminance: Luminance) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let (width, height) = img.dimensions();
+    let lumas = img
+        .pixels()
+        .map(|pixel| brightness(pixel, luminance) * 255.0)
+        .collect::<Vec<_>>();
+    let thresholds = match threshold {
+        Threshold::Fixed(level) => vec![level; lumas.len()],
+        Threshold::Otsu => vec![otsu_threshold(&lumas); lumas.len()],
+        Threshold::Mean { window, offset } => box_mean(&lumas, width, window / 2)
+            .into_iter()
+            .map(|mean| mean - offset)
+            .collect(),
+        Threshold::Gaussian { window, offset } => gaussian_mean(&lumas, width, window / 2)
+            .into_iter()
+            .map(|mean| mean - offset)
+            .collect(),
+    };
+
+    let mut img_buf = ImageBuffer::new(width, height);
+    let (black, white) = (S::DEFAULT_MIN_VALUE, S::DEFAULT_MAX_VALUE);
+
+    for (i, (x, y, pixel)) in img.enumerate_pixels().enumerate() {
+        let new_pixel = if lumas[i] >= thresholds[i] {
+            Rgba([white, white, white, pixel[3]])
+        } else {
+            Rgba([black, black, black, pixel[3]])
+        };
+
+        img_buf.put_pixel(x, y, new_pixel);
+    }
+    img_buf
+}
+
+/// Finds the level splitting `lumas` in 0..255 range with the largest variance between
+/// the darker and the brighter class
+fn otsu_threshold(lumas: &[f32]) -> f32 {
+    let mut histogram = [0.0f64; 256];
+    for &luma in lumas {
+        histogram[luma.round().clamp(0.0, 255.0) as usize] += 1.0;
+    }
+    let total = lumas.len() as f64;
+    let sum = histogram
+        .iter()
+        .enumerate()
+        .map(|(level, &count)| level as f64 * count)
+        .sum::<f64>();
+
+    let (mut dark_count, mut dark_sum) = (0.0, 0.0);
+    let (mut best, mut best_variance) = (0, -1.0);
+    for (level, &count) in histogram.iter().enumerate() {
+        dark_count += count;
+        dark_sum += level as f64 * count;
+        let bright_count = total - dark_count;
+        if dark_count == 0.0 || bright_count == 0.0 {
+            continue;
+        }
+        let difference = dark_sum / dark_count - (sum - dark_sum) / bright_count;
+        let variance = dark_count * bright_count * difference * difference;
+        if variance > best_variance {
+            (best, best_variance) = (level, variance);
+        }
+    }
+    // the dark class takes levels up to `best` inclusive
+    best as f32 + 0.5
+}
+
+/// Mean of the square reaching `radius` pixels around every value of the row-major
+/// `values`, computed over a summed-area table, the square is cut at the edges
+fn box_mean(values: &[f32], width: u32, radius: u32) -> Vec<f32> {
+    let (width, radius) = (width as usize, radius as usize);
+    if width == 0 {
+        return Vec::new();
+    }
+    let height = values.len() / width;
+
+    let mut table = vec![0.0f64; (width + 1) * (height + 1)];
+    for y in 0..height {
+        for x in 0..width {
+            table[(y + 1) * (width + 1) + x + 1] = values[y * width + x] as f64
+                + table[y * (width + 1) + x + 1]
+                + table[(y + 1) * (width + 1) + x]
+                - table[y * (width + 1) + x];
+        }
+    }
+
+    let mut means = Vec::with_capacity(values.len());
+    for y in 0..height {
+        let (top, bottom) = (y.saturating_sub(radius), (y + radius + 1).min(height));
+        for x in 0..width {
+            let (left, right) = (x.saturating_sub(radius), (x + radius + 1).min(width));
+            let sum = table[bottom * (width + 1) + right]
+                - table[top * (width + 1) + right]
+                - table[bottom * (width + 1) + left]
+                + table[top * (width + 1) + left];
+            means.push((sum / ((right - left) * (bottom - top)) as f64) as f32);
+        }
+    }
+    means
+}
+
+/// Gaussian weighted mean reaching `radius` pixels around every value of the row-major
+/// `values`, the kernel covers three sigmas and is cut at the edges
+fn gaussian_mean(values: &[f32], width: u32, radius: u32) -> Vec<f32> {
+    let (width, radius) = (width as usize, radius as i64);
+    if width == 0 {
+        return Vec::new();
+    }
+    let height = values.len() / width;
+    let sigma = (radius as f32 / 3.0).max(0.5);
+    let kernel = (-radius..=radius)
+        .map(|d| (-(d * d) as f32 / (2.0 * sigma * sigma)).exp())
+        .collect::<Vec<_>>();
+
+    // separable blur, rows first, then columns
+    let blur = |values: &[f32], along_rows: bool| {
+        let mut blurred = vec![0.0; values.len()];
+        for y in 0..height {
+            for x in 0..width {
+                let (mut sum, mut weights) = (0.0, 0.0);
+                for (d, weight) in (-radius..=radius).zip(&kernel) {
+                    let (nx, ny) = if along_rows {
+                        (x as i64 + d, y as i64)
+                    } else {
+                        (x as i64, y as i64 + d)
+                    };
+                    if nx < 0 || ny < 0 || nx >= width as i64 || ny >= height as i64 {
+                        continue;
+                    }
+                    sum += values[ny as usize * width + nx as usize] * weight;
+                    weights += weight;
+                }
+                blurred[y * width + x] = sum / weights;
+            }
+        }
+        blurred
+    };
+    blur(&blur(values, true), false)
+}
+
+/// Dithering method spreading the quantization error or hiding it in a pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dither {
+    #[default]
+    FloydSteinberg,
+    /// Spreads only 3/4 of the error, which keeps more contrast
+    Atkinson,
+    JarvisJudiceNinke,
+    Sierra,
+    /// Ordered dithering with the given side of the Bayer matrix, 2, 4 or 8
+    Bayer(u32),
+}
+
+impl FromStr for Dither {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "floyd-steinberg" => Ok(Dither::FloydSteinberg),
+            "atkinson" => Ok(Dither::Atkinson),
+            "jarvis-judice-ninke" => Ok(Dither::JarvisJudiceNinke),
+            "sierra" => Ok(Dither::Sierra),
+            "bayer2" => Ok(Dither::Bayer(2)),
+            "bayer4" => Ok(Dither::Bayer(4)),
+            "bayer8" => Ok(Dither::Bayer(8)),
+            _ => Err(format!("unknown dithering `{s}`")),
+        }
+    }
+}
+
+impl Dither {
+    /// Neighbours receiving the error as x and y offsets with their share of it
+    fn kernel(self) -> &'static [(i32, i32, f32)] {
+        match self {
+            Dither::FloydSteinberg => &[
+                (1, 0, 7.0 / 16.0),
+                (-1, 1, 3.0 / 16.0),
+                (0, 1, 5.0 / 16.0),
+                (1, 1, 1.0 / 16.0),
+            ],
+            Dither::Atkinson => &[
+                (1, 0, 1.0 / 8.0),
+                (2, 0, 1.0 / 8.0),
+                (-1, 1, 1.0 / 8.0),
+                (0, 1, 1.0 / 8.0),
+                (1, 1, 1.0 / 8.0),
+                (0, 2, 1.0 / 8.0),
+            ],
+            Dither::JarvisJudiceNinke => &[
+                (1, 0, 7.0 / 48.0),
+                (2, 0, 5.0 / 48.0),
+                (-2, 1, 3.0 / 48.0),
+                (-1, 1, 5.0 / 48.0),
+                (0, 1, 7.0 / 48.0),
+                (1, 1, 5.0 / 48.0),
+                (2, 1, 3.0 / 48.0),
+                (-2, 2, 1.0 / 48.0),
+                (-1, 2, 3.0 / 48.0),
+                (0, 2, 5.0 / 48.0),
+                (1, 2, 3.0 / 48.0),
+                (2, 2, 1.0 / 48.0),
+            ],
+            Dither::Sierra => &[
+                (1, 0, 5.0 / 32.0),
+                (2, 0, 3.0 / 32.0),
+                (-2, 1, 2.0 / 32.0),
+                (-1, 1, 4.0 / 32.0),
+                (0, 1, 5.0 / 32.0),
+                (1, 1, 4.0 / 32.0),
+                (2, 1, 2.0 / 32.0),
+                (-1, 2, 2.0 / 32.0),
+                (0, 2, 3.0 / 32.0),
+                (1, 2, 2.0 / 32.0),
+            ],
+            Dither::Bayer(_) => &[],
+        }
+    }
+}
+
+/// Bayer threshold matrix with the side of `size`, rounded up to a power of two,
+/// thresholds are spread evenly in 0..1 range
+fn bayer_matrix(size: u32) -> Vec<Vec<f32>> {
+    let mut matrix = vec![vec![0u32]];
+    while (matrix.len() as u32) < size {
+        let n = matrix.len();
+        let mut next = vec![vec![0; n * 2]; n * 2];
+        for (y, row) in matrix.iter().enumerate() {
+            for (x, &value) in row.iter().enumerate() {
+                next[y][x] = 4 * value;
+                next[y][x + n] = 4 * value + 2;
+                next[y + n][x] = 4 * value + 3;
+                next[y + n][x + n] = 4 * value + 1;
+            }
+        }
+        matrix = next;
+    }
+    let cells = (matrix.len() * matrix.len()) as f32;
+    matrix
+        .iter()
+        .map(|row| row.iter().map(|&v| (v as f32 + 0.5) / cells).collect())
+        .collect()
+}
+
+/// Replaces every color of the row-major `colors` by the one `nearest` picks, dithering the
+/// difference with the `method`
+///
+/// Ordered dithering shifts colors by up to half of the `spread` before picking,
+/// serpentine scanning goes through every other row backwards
+fn dither_colors<F>(
+    colors: &mut [[f32; 3]],
+    width: u32,
+    method: Dither,
+    serpentine: bool,
+    spread: f32,
+    mut nearest: F,
+) where
+    F: FnMut([f32; 3]) -> [f32; 3],
+{
+    let width = width as usize;
+    if width == 0 {
+        return;
+    }
+    let height = colors.len() / width;
+
+    if let Dither::Bayer(size) = method {
+        let matrix = bayer_matrix(size);
+        let n = matrix.len();
+        for (i, color) in colors.iter_mut().enumerate() {
+            let offset = (matrix[(i / width) % n][(i % width) % n] - 0.5) * spread;
+            *color = nearest(color.map(|c| c - offset));
+        }
+        return;
+    }
+
+    let kernel = method.kernel();
+    for y in 0..height {
+        let backwards = serpentine && y % 2 == 1;
+        for step in 0..width {
+            let x = if backwards { width - 1 - step } else { step };
+            let old = colors[y * width + x];
+            let new = nearest(old);
+            colors[y * width + x] = new;
+
+            for &(dx, dy, share) in kernel {
+                let dx = if backwards { -dx } else { dx };
+                let (nx, ny) = (x as i64 + dx as i64, y + dy as usize);
+                if nx < 0 || nx >= width as i64 || ny >= height {
+                    continue;
+                }
+                let neighbour = &mut colors[ny * width + nx as usize];
+                for c in 0..3 {
+                    neighbour[c] += (old[c] - new[c]) * share;
+                }
+            }
+        }
+    }
+}
+
+/// Dithers the image into black and white by its `luminance`, alpha channel is kept
+///
+/// Error diffusion methods scan the rows left to right, or in both directions with
+/// `serpentine`, which breaks up the diagonal worms of the patterns
+pub fn dither<S>(
+    img: &Buffer<S>,
+    method: Dither,
+    serpentine: bool,
+    luminance: Luminance,
+) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let mut levels = img
+        .pixels()
+        .map(|pixel| [brightness(pixel, luminance).min(1.0); 3])
+        .collect::<Vec<_>>();
+    dither_colors(
+        &mut levels,
+        img.width(),
+        method,
+        serpentine,
+        1.0,
+        |[l, ..]| [if l >= 0.5 { 1.0 } else { 0.0 }; 3],
+    );
+
+    let (black, white) = (S::DEFAULT_MIN_VALUE, S::DEFAULT_MAX_VALUE);
+    let mut dithered = img.clone();
+    for (pixel, [level, ..]) in dithered.pixels_mut().zip(levels) {
+        let value = if level > 0.0 { white } else { black };
+        *pixel = Rgba([value, value, value, pixel[3]]);
+    }
+    dithered
+}
+
+/// Picks at most `count` colors representing the image by the `method`,
+/// fully transparent pixels are left out
+pub fn extract_palette<S>(img: &Buffer<S>, count: usize, method: PaletteMethod) -> Vec<Rgb<u8>>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let colors = img
+        .pixels()
+        .filter(|pixel| to_f32(pixel[3]) > 0.0)
+        .map(|pixel| [0, 1, 2].map(|i| to_u8_scale(pixel[i]).clamp(0.0, 255.0) as u8))
+        .collect::<Vec<_>>();
+    palette_of(&colors, count, method)
+}
+
+/// Picks at most `count` dominant colors of the image with their shares of its visible pixels,
+/// the most common color goes first
+pub fn dominant_colors<S>(
+    img: &Buffer<S>,
+    count: usize,
+    method: PaletteMethod,
+) -> Vec<(Rgb<u8>, f32)>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let palette = extract_palette(img, count, method);
+    let mut counts = vec![0usize; palette.len()];
+    for pixel in img.pixels().filter(|pixel| to_f32(pixel[3]) > 0.0) {
+        let color = [0, 1, 2].map(|i| to_u8_scale(pixel[i]).min(255.0));
+        counts[nearest(&palette, color)] += 1;
+    }
+    let total = counts.iter().sum::<usize>().max(1) as f32;
+
+    let mut colors = palette
+        .into_iter()
+        .zip(counts)
+        .filter(|&(_, count)| count > 0)
+        .map(|(color, count)| (color, count as f32 / total))
+        .collect::<Vec<_>>();
+    colors.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    colors
+}
+
+/// Replaces every color of the image by the nearest one of the `palette`, alpha channel is kept
+///
+/// With `dither` the difference is spread over the neighbours or hidden in a Bayer pattern
+pub fn quantize<S>(
+    img: &Buffer<S>,
+    palette: &[Rgb<u8>],
+    dither: Option<Dither>,
+    serpentine: bool,
+) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    if palette.is_empty() {
+        return img.clone();
+    }
+    let mut colors = img
+        .pixels()
+        .map(|pixel| [0, 1, 2].map(|i| to_u8_scale(pixel[i]).min(255.0)))
+        .collect::<Vec<_>>();
+    let pick = |color: [f32; 3]| palette[nearest(palette, color)].0.map(|c| c as f32);
+    match dither {
+        // ordered dithering shifts colors by about the distance between the palette levels
+        Some(method) => {
+            let spread = 255.0 / (palette.len() as f32).cbrt();
+            dither_colors(&mut colors, img.width(), method, serpentine, spread, pick);
+        }
+        None => colors.iter_mut().for_each(|color| *color = pick(*color)),
+    }
+
+    let mut quantized = img.clone();
+    for (pixel, color) in quantized.pixels_mut().zip(colors) {
+        let alpha = pixel[3];
+        *pixel = Rgba([
+            from_f32(color[0] / 255.0 * max::<S>()),
+            from_f32(color[1] / 255.0 * max::<S>()),
+            from_f32(color[2] / 255.0 * max::<S>()),
+            alpha,
+        ]);
+    }
+    quantized
+}
+
+/// Grayscales the image by its `luminance` keeping its alpha channel
+pub fn grayscale<S>(img: &Buffer<S>, luminance: Luminance) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let (width, height) = img.dimensions();
+    let mut img_buf = ImageBuffer::new(width, height);
+    let max = max::<S>();
+
+    for (x, y, pixel) in img.enumerate_pixels() {
+        let grayscale_value = from_f32(brightness(pixel, luminance) * max);
+
+        let grayscale_pixel = Rgba([grayscale_value, grayscale_value, grayscale_value, pixel[3]]);
+
+        img_buf.put_pixel(x, y, grayscale_pixel);
+    }
+    img_buf
+}
+
+/// Flattens the image onto a solid `background`, making it opaque
+///
+/// Semi-transparent background is blended under the image and stays semi-transparent
+pub fn flatten<S>(img: &Buffer<S>, background: Rgba<u8>) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let mut flat = Buffer::from_pixel(img.width(), img.height(), convert_color(background));
+    imageops::overlay(&mut flat, img, 0, 0);
+    flat
+}
+
+/// Curses the image stretching and squishing it by 1.5 & 0.5 correspondingly
+///
+/// After that pixelate the image with pixel size of 5, alpha channel is resampled like the colors
+pub fn curse<S>(img: &Buffer<S>) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let stretch_x = 1.5;
+    let stretch_y = 0.5;
+
+    let (old_width, old_height) = img.dimensions();
+
+    let new_width = (old_width as f32 * stretch_x) as u32;
+    let new_height = (old_height as f32 * stretch_y) as u32;
+
+    let mut cursed = ImageBuffer::new(new_width, new_height);
+
+    for (new_x, new_y, pixel) in cursed.enumerate_pixels_mut() {
+        let old_x = (new_x as f32 * (old_width as f32 / new_width as f32)) as u32;
+        let old_y = (new_y as f32 * (old_height as f32 / new_height as f32)) as u32;
+
+        if let Some(old_pixel) = img.get_pixel_checked(old_x, old_y) {
+            *pixel = *old_pixel;
+        }
+    }
+
+    pixelate(&cursed, (5, 5))
+}
+
+/// ZXC the image. Ultimate dead inside happens here.
+///
+/// Wish you the worst of luck.
+pub fn zxc<S>(img: &Buffer<S>) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    let mid_curse: Buffer<S> = curse(img);
+    monochrome_ugly(&mid_curse, Threshold::Fixed(125.0), Luminance::default())
+}
+
+/// Operator bringing HDR values above 1 into the displayable 0..1 range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ToneMap {
+    /// Cuts everything above 1, highlights are lost
+    #[default]
+    Clamp,
+    /// `x / (1 + x)`, compresses highlights and darkens midtones
+    Reinhard,
+    /// Filmic curve approximating ACES, keeps contrast of the midtones
+    Aces,
+}
+
+impl FromStr for ToneMap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clamp" => Ok(ToneMap::Clamp),
+            "reinhard" => Ok(ToneMap::Reinhard),
+            "aces" => Ok(ToneMap::Aces),
+            _ => Err(format!("unknown tone mapping `{s}`")),
+        }
+    }
+}
+
+/// Tone maps color channels of the HDR image, alpha channel is only clamped
+pub fn tone_map(img: &ImageF32, operator: ToneMap) -> ImageF32 {
+    let curve = |x: f32| match operator {
+        ToneMap::Clamp => x,
+        ToneMap::Reinhard => x / (1.0 + x),
+        ToneMap::Aces => (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14),
+    };
+
+    let mut mapped = img.clone();
+    for pixel in mapped.pixels_mut() {
+        for (i, channel) in pixel.0.iter_mut().enumerate() {
+            let value = channel.max(0.0);
+            *channel = if i < 3 { curve(value) } else { value }.min(1.0);
+        }
+    }
+    mapped
+}
+
+/// Converts 8-bit color into the channel type
+fn convert_color<S: Primitive>(color: Rgba<u8>) -> Rgba<S> {
+    Rgba(color.0.map(|c| from_f32(c as f32 / 255.0 * max::<S>())))
+}
+
+/// Luminance of the pixel in 0..1 range, HDR pixels can go above it
+fn brightness<S: Primitive>(pixel: &Rgba<S>, luminance: Luminance) -> f32 {
+    luminance.of([0, 1, 2].map(|i| to_f32(pixel[i]) / max::<S>()))
+}
+
+/// Maximum value of the channel type, 1 for floats
+fn max<S: Primitive>() -> f32 {
+    to_f32(S::DEFAULT_MAX_VALUE)
+}
+
+fn to_f32<S: Primitive>(value: S) -> f32 {
+    <f32 as NumCast>::from(value).unwrap_or_default()
+}
+
+/// Channel value scaled into 0..255 range of 8-bit images
+fn to_u8_scale<S: Primitive>(value: S) -> f32 {
+    to_f32(value) / max::<S>() * 255.0
+}
+
+/// Casts the value back into the channel type, saturating at its bounds
+///
+/// Integer channels are rounded to the nearest value, so unchanged colors come back as they were.
+/// Floats are only cut below zero, so HDR values above 1 survive
+fn from_f32<S: Primitive>(value: f32) -> S {
+    let value = if max::<S>() > 1.0 {
+        value.round()
+    } else {
+        value
+    };
+    NumCast::from(value.max(0.0)).unwrap_or(S::DEFAULT_MAX_VALUE)
+}
+
+/// Runs an imageops filter, which clips channels at 1 for floats, without losing HDR highlights
+///
+/// Image brighter than that is scaled down into the range for the filter and back afterwards,
+/// integer images are passed as they are
+fn unclipped<S, F>(img: &Buffer<S>, filter: F) -> Buffer<S>
+where
+    S: Primitive + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+    F: Fn(&Buffer<S>) -> Buffer<S>,
+{
+    // integer channels can't go above their maximum
+    if max::<S>() > 1.0 {
+        return filter(img);
+    }
+    let peak = img
+        .pixels()
+        .flat_map(|p| p.0[..3].iter())
+        .map(|&c| to_f32(c))
+        .fold(0.0, f32::max);
+    if peak <= 1.0 {
+        return filter(img);
+    }
+
+    let scale_colors = |img: &Buffer<S>, k: f32| {
+        let mut scaled = img.clone();
+        for pixel in scaled.pixels_mut() {
+            for channel in pixel.0.iter_mut().take(3) {
+                *channel = from_f32(to_f32(*channel) * k);
+            }
+        }
+        scaled
+    };
+    scale_colors(&filter(&scale_colors(img, 1.0 / peak)), peak)
+}
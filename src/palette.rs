@@ -0,0 +1,306 @@
+use image::{Rgb, Rgba, RgbaImage};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// How `extract_palette` picks the colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PaletteMethod {
+    /// Splits the color space into boxes of equal population, fast and deterministic
+    #[default]
+    MedianCut,
+    /// Refines the median cut colors by k-means clustering, closer to the image but slower
+    KMeans,
+}
+
+impl FromStr for PaletteMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "median-cut" => Ok(PaletteMethod::MedianCut),
+            "k-means" => Ok(PaletteMethod::KMeans),
+            _ => Err(format!("unknown palette method `{s}`")),
+        }
+    }
+}
+
+/// Names of the built-in palettes
+pub const BUILTIN: &[&str] = &["cga", "gameboy", "pico-8", "web-safe"];
+
+/// Gets a built-in palette by its name, see `BUILTIN`
+pub fn builtin(name: &str) -> Option<Vec<Rgb<u8>>> {
+    let hex: &[u32] = match name {
+        "cga" => &[
+            0x000000, 0x0000aa, 0x00aa00, 0x00aaaa, 0xaa0000, 0xaa00aa, 0xaa5500, 0xaaaaaa,
+            0x555555, 0x5555ff, 0x55ff55, 0x55ffff, 0xff5555, 0xff55ff, 0xffff55, 0xffffff,
+        ],
+        "gameboy" => &[0x0f380f, 0x306230, 0x8bac0f, 0x9bbc0f],
+        "pico-8" => &[
+            0x000000, 0x1d2b53, 0x7e2553, 0x008751, 0xab5236, 0x5f574f, 0xc2c3c7, 0xfff1e8,
+            0xff004d, 0xffa300, 0xffec27, 0x00e436, 0x29adff, 0x83769c, 0xff77a8, 0xffccaa,
+        ],
+        "web-safe" => {
+            let levels = [0x00, 0x33, 0x66, 0x99, 0xcc, 0xff];
+            let mut colors = Vec::with_capacity(216);
+            for r in levels {
+                for g in levels {
+                    colors.extend(levels.map(|b| Rgb([r, g, b])));
+                }
+            }
+            return Some(colors);
+        }
+        _ => return None,
+    };
+    Some(
+        hex.iter()
+            .map(|&c| Rgb([(c >> 16) as u8, (c >> 8) as u8, c as u8]))
+            .collect(),
+    )
+}
+
+/// Parses a GIMP `.gpl` palette or a list of hex colors
+///
+/// Hex colors like `#ff8800` or `ff8800` are separated by whitespace or commas,
+/// lines starting with `;` or `//` are comments
+pub fn parse_palette(text: &str) -> Result<Vec<Rgb<u8>>, String> {
+    let colors = if text.trim_start().starts_with("GIMP Palette") {
+        parse_gpl(text)?
+    } else {
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with(';') && !line.starts_with("//"))
+            .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ','))
+            .filter(|word| !word.is_empty())
+            .map(|word| {
+                let hex = word.trim_start_matches('#');
+                match u32::from_str_radix(hex, 16) {
+                    Ok(c) if hex.len() == 6 => Ok(Rgb([(c >> 16) as u8, (c >> 8) as u8, c as u8])),
+                    _ => Err(format!("`{word}` is not a hex color like #ff8800")),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    if colors.is_empty() {
+        return Err("palette has no colors".to_string());
+    }
+    Ok(colors)
+}
+
+/// Parses colors of a `.gpl` file, lines of `R G B` and an optional name after the header
+fn parse_gpl(text: &str) -> Result<Vec<Rgb<u8>>, String> {
+    text.lines()
+        .skip(1)
+        .map(str::trim)
+        .filter(|line| {
+            !line.is_empty()
+                && !line.starts_with('#')
+                && !line.starts_with("Name:")
+                && !line.starts_with("Columns:")
+        })
+        .map(|line| {
+            let channels = line
+                .split_whitespace()
+                .take(3)
+                .map(|c| c.parse::<u8>())
+                .collect::<Result<Vec<_>, _>>();
+            match channels.as_deref() {
+                Ok(&[r, g, b]) => Ok(Rgb([r, g, b])),
+                _ => Err(format!("`{line}` is not a color like 255 136 0")),
+            }
+        })
+        .collect()
+}
+
+/// Writes the colors as a GIMP `.gpl` palette with the given name
+pub fn to_gpl(colors: &[Rgb<u8>], name: &str) -> String {
+    let mut gpl = format!("GIMP Palette\nName: {name}\nColumns: {}\n#\n", colors.len());
+    for color in colors {
+        let [r, g, b] = color.0;
+        gpl += &format!("{r:3} {g:3} {b:3}\t{}\n", to_hex(color));
+    }
+    gpl
+}
+
+/// Writes the colors as an Adobe Swatch Exchange `.ase` file, named by their hex values
+pub fn to_ase(colors: &[Rgb<u8>]) -> Vec<u8> {
+    let mut ase = b"ASEF".to_vec();
+    ase.extend(1u16.to_be_bytes());
+    ase.extend(0u16.to_be_bytes());
+    ase.extend((colors.len() as u32).to_be_bytes());
+    for color in colors {
+        // UTF-16 name ending with a zero
+        let name = to_hex(color).encode_utf16().chain([0]).collect::<Vec<_>>();
+        let length = 2 + name.len() * 2 + 4 + 3 * 4 + 2;
+
+        ase.extend(1u16.to_be_bytes()); // color entry
+        ase.extend((length as u32).to_be_bytes());
+        ase.extend((name.len() as u16).to_be_bytes());
+        ase.extend(name.iter().flat_map(|unit| unit.to_be_bytes()));
+        ase.extend(b"RGB ");
+        for channel in color.0 {
+            ase.extend((channel as f32 / 255.0).to_be_bytes());
+        }
+        ase.extend(2u16.to_be_bytes()); // normal, neither global nor spot color
+    }
+    ase
+}
+
+/// Renders the colors as stripes as wide as their shares, each one is at least a pixel wide
+pub fn swatch(colors: &[(Rgb<u8>, f32)], width: u32, height: u32) -> RgbaImage {
+    let total = colors.iter().map(|(_, share)| share).sum::<f32>();
+    let mut image = RgbaImage::new(width, height);
+    let mut left = 0;
+    for (i, &(color, share)) in colors.iter().enumerate() {
+        let right = if i + 1 == colors.len() {
+            width
+        } else {
+            (left + (share / total * width as f32).round() as u32)
+                .max(left + 1)
+                .min(width)
+        };
+        let [r, g, b] = color.0;
+        for x in left..right {
+            for y in 0..height {
+                image.put_pixel(x, y, Rgba([r, g, b, 255]));
+            }
+        }
+        left = right;
+    }
+    image
+}
+
+/// Formats the color as `#rrggbb`
+pub fn to_hex(color: &Rgb<u8>) -> String {
+    let [r, g, b] = color.0;
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Picks at most `count` colors representing the given ones by the `method`
+pub fn palette_of(colors: &[[u8; 3]], count: usize, method: PaletteMethod) -> Vec<Rgb<u8>> {
+    let mut weights = HashMap::new();
+    for &color in colors {
+        *weights.entry(color).or_insert(0u64) += 1;
+    }
+    // HashMap iterates in random order, sorting keeps the palette the same on every run
+    let mut weighted = weights.into_iter().collect::<Vec<_>>();
+    weighted.sort_unstable();
+
+    let palette = median_cut(weighted.clone(), count);
+    match method {
+        PaletteMethod::MedianCut => palette,
+        PaletteMethod::KMeans => k_means(&weighted, palette),
+    }
+}
+
+/// Splits the colors into `count` boxes, cutting the widest one at its median every time,
+/// and averages every box
+fn median_cut(colors: Vec<([u8; 3], u64)>, count: usize) -> Vec<Rgb<u8>> {
+    // widest channel of the box and how wide it is
+    let extent = |colors: &[([u8; 3], u64)]| {
+        (0..3)
+            .map(|c| {
+                let (low, high) = colors.iter().fold((255, 0), |(low, high), (color, _)| {
+                    (color[c].min(low), color[c].max(high))
+                });
+                (c, high.saturating_sub(low))
+            })
+            .max_by_key(|&(_, range)| range)
+            .unwrap_or((0, 0))
+    };
+
+    let mut boxes = vec![colors];
+    while boxes.len() < count {
+        let Some((index, channel)) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, colors)| colors.len() > 1)
+            .map(|(i, colors)| (i, extent(colors)))
+            .max_by_key(|&(_, (_, range))| range)
+            .map(|(i, (channel, _))| (i, channel))
+        else {
+            break;
+        };
+
+        let mut colors = boxes.swap_remove(index);
+        // ties are broken by the whole color, so the boxes don't depend on their order
+        colors.sort_unstable_by_key(|&(color, _)| (color[channel], color));
+        let half = colors.iter().map(|(_, w)| w).sum::<u64>() / 2;
+        let mut seen = 0;
+        let median = colors
+            .iter()
+            .position(|(_, w)| {
+                seen += w;
+                seen > half
+            })
+            .unwrap_or(0)
+            .clamp(1, colors.len() - 1);
+        let upper = colors.split_off(median);
+        boxes.push(colors);
+        boxes.push(upper);
+    }
+
+    boxes
+        .iter()
+        .filter(|colors| !colors.is_empty())
+        .map(|colors| {
+            let total = colors.iter().map(|(_, w)| w).sum::<u64>() as f64;
+            Rgb([0, 1, 2].map(|c| {
+                let sum = colors
+                    .iter()
+                    .map(|(color, w)| color[c] as f64 * *w as f64)
+                    .sum::<f64>();
+                (sum / total).round() as u8
+            }))
+        })
+        .collect()
+}
+
+/// Moves every color of the palette to the mean of the colors nearest to it,
+/// until none of them moves
+fn k_means(colors: &[([u8; 3], u64)], mut palette: Vec<Rgb<u8>>) -> Vec<Rgb<u8>> {
+    const ITERATIONS: usize = 16;
+
+    for _ in 0..ITERATIONS {
+        let mut sums = vec![([0.0f64; 3], 0.0f64); palette.len()];
+        for &(color, weight) in colors {
+            let (sum, total) = &mut sums[nearest(&palette, color.map(|c| c as f32))];
+            for c in 0..3 {
+                sum[c] += color[c] as f64 * weight as f64;
+            }
+            *total += weight as f64;
+        }
+
+        let moved = palette
+            .iter()
+            .zip(&sums)
+            .map(|(old, (sum, total))| {
+                if *total == 0.0 {
+                    // a color nobody is nearest to stays where it is
+                    *old
+                } else {
+                    Rgb(sum.map(|s| (s / total).round() as u8))
+                }
+            })
+            .collect::<Vec<_>>();
+        if moved == palette {
+            break;
+        }
+        palette = moved;
+    }
+    palette
+}
+
+/// Index of the palette color nearest to the one with channels in 0..255 range
+pub fn nearest(palette: &[Rgb<u8>], color: [f32; 3]) -> usize {
+    palette
+        .iter()
+        .map(|p| {
+            (0..3)
+                .map(|c| (p[c] as f32 - color[c]).powi(2))
+                .sum::<f32>()
+        })
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
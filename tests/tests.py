@@ -1,5 +1,9 @@
+import colorsys
 import os
+import shutil
+import struct
 import subprocess
+import zlib
 
 
 current_file_path = os.path.dirname(os.path.abspath(__file__))
@@ -15,6 +19,19 @@ def run_rust_cli(args):
     except subprocess.CalledProcessError as e:
         return e.output
 
+def write_ramp(path):
+    """
+    Write 8-bit PPM image holding every channel value, so rounding errors can't hide.
+    """
+    with open(path, 'wb') as image:
+        image.write(b'P6\n256 3\n255\n' + bytes(v for i in range(768) for v in [i % 256, i * 7 % 256, 255 - i % 256]))
+
+def pixels_of(args):
+    """
+    Run the Rust CLI application writing PAM to stdout and return its bytes.
+    """
+    return subprocess.run([current_file_path + '/climp'] + args[:1] + ['-o', '-', '--format', 'pam'] + args[1:], capture_output=True).stdout
+
 def test_blur_command():
     args = [image_path, '-o', './out.png','blur', '-r', '5']
     output = run_rust_cli(args)
@@ -26,6 +43,8 @@ def test_pixelate_command():
     output = run_rust_cli(args)
     assert "Pixelated image saved as" in output
     assert os.path.exists('./out.png')
+    result = subprocess.run([current_file_path + '/climp', image_path, '-o', './out.png', 'pixelate', '-p', '0'], capture_output=True, text=True)
+    assert result.returncode == 2
     print("\x1b[32mPIXELATE\x1b[0m COMMAND TEST PASSED")
 
 def test_mirror_command():
@@ -47,6 +66,10 @@ def test_rotate_command():
     output = run_rust_cli(args)
     assert "Rotated image saved as" in output
     assert os.path.exists('./out.png')
+    output = run_rust_cli([image_path, '-o', './out.png', 'rotate', '-d', '45', '--background', 'white'])
+    assert "Rotated image saved as" in output
+    output = run_rust_cli([image_path, '-o', './out.png', 'rotate', '-d', '-30', '--canvas', 'crop', '--interpolation', 'bicubic'])
+    assert "Rotated image saved as" in output
     print("\x1b[32mROTATE\x1b[0m COMMAND TEST PASSED")
 
 def test_grayscale_command():
@@ -68,8 +91,342 @@ def test_scale_command():
     output = run_rust_cli(args)
     assert "Scaled image saved as" in output
     assert os.path.exists('./out.png')
+    with open('./out.png', 'rb') as image:
+        width, height = struct.unpack('>II', image.read(24)[16:24])
+    output = run_rust_cli([image_path, '-o', './half.png', 'scale', '--width', str(width // 4), '--filter', 'catmull-rom'])
+    assert "Scaled image saved as" in output
+    with open('./half.png', 'rb') as image:
+        assert struct.unpack('>II', image.read(24)[16:24]) == (width // 4, height // 4)
+    for size in [['-s', '100000'], ['--width', '4000000000', '--height', '4000000000', '--mode', 'exact']]:
+        result = subprocess.run([current_file_path + '/climp', image_path, '-o', './out.png', 'scale'] + size, capture_output=True, text=True)
+        assert result.returncode == 2
+        assert "image too large" in result.stderr
     print("\x1b[32mSCALE\x1b[0m COMMAND TEST PASSED")
 
+def test_pipeline_command():
+    args = [image_path, '-o', './out.png', 'rotate', 'grayscale', 'blur', '-r', '3']
+    output = run_rust_cli(args)
+    assert "Rotated, Grayscale, Blurred image saved as" in output
+    assert os.path.exists('./out.png')
+    print("\x1b[32mPIPELINE\x1b[0m COMMAND TEST PASSED")
+
+def test_batch_command():
+    run_rust_cli([image_path, '-o', './photo_copy.jpg', 'mirror'])
+    args = [image_path, './photo_copy.jpg', '--out-dir', './batch', 'grayscale']
+    output = run_rust_cli(args)
+    assert "Processed 2 files: 2 succeeded, 0 failed" in output
+    assert os.path.exists('./batch/photo.jpg')
+    os.remove('./photo_copy.jpg')
+    for folder in ['./same/a', './same/b']:
+        os.makedirs(folder, exist_ok=True)
+        run_rust_cli([image_path, '-o', folder + '/x.png', 'mirror'])
+    output = run_rust_cli(['./same/a/x.png', './same/b/x.png', '--out-dir', './batch', 'mirror'])
+    assert "would be written by 2 inputs" in output
+    assert "0 succeeded, 2 failed" in output
+    output = run_rust_cli(['./same', '-R', '--out-dir', './batch', 'mirror'])
+    assert "Processed 2 files: 2 succeeded, 0 failed" in output
+    assert os.path.exists('./batch/a/x.png') and os.path.exists('./batch/b/x.png')
+    shutil.rmtree('./same')
+    print("\x1b[32mBATCH\x1b[0m COMMAND TEST PASSED")
+
+def test_missing_file_exit_code():
+    result = subprocess.run([current_file_path + '/climp', './missing.jpg', 'rotate'], capture_output=True, text=True)
+    assert result.returncode == 3
+    assert "Failed to access" in result.stderr
+    result = subprocess.run([current_file_path + '/climp', image_path], capture_output=True, text=True)
+    assert result.returncode == 2
+    assert "no command given" in result.stderr
+    with open('./tiny.ppm', 'wb') as image:
+        image.write(b'P6\n1 1\n255\n' + bytes(3))
+    result = subprocess.run([current_file_path + '/climp', './tiny.ppm', '-o', './out.png', 'curse'], capture_output=True, text=True)
+    assert result.returncode == 5
+    assert "Failed to encode" in result.stderr
+    os.remove('./tiny.ppm')
+    print("\x1b[32mMISSING FILE\x1b[0m EXIT CODE TEST PASSED")
+
+def test_run_recipe_command():
+    with open('./recipe.toml', 'w') as recipe:
+        recipe.write(f'inputs = ["{image_path}"]\n\n[output]\npath = "./recipe.png"\n\n[[steps]]\nop = "rotate"\n\n[[steps]]\nop = "blur"\nblur_radius = 2\n')
+    output = run_rust_cli(['run', './recipe.toml'])
+    assert "Rotated, Blurred image saved as" in output
+    assert os.path.exists('./recipe.png')
+    print("\x1b[32mRUN\x1b[0m RECIPE COMMAND TEST PASSED")
+
+def test_stdin_stdout_command():
+    with open(image_path, 'rb') as image:
+        result = subprocess.run([current_file_path + '/climp', '-', '-o', '-', '--format', 'png', 'grayscale'], input=image.read(), capture_output=True)
+    assert result.returncode == 0
+    assert result.stdout.startswith(b'\x89PNG')
+    with open('./one.ppm', 'wb') as image:
+        image.write(b'P6\n4 4\n255\n' + bytes(range(48)))
+    for op in [['pixelate', '-p', '5'], ['curse']]:
+        result = subprocess.run([current_file_path + '/climp', './one.ppm', '-o', '-', '--format', 'png'] + op, capture_output=True)
+        assert result.returncode == 0
+        assert result.stdout.startswith(b'\x89PNG')
+    os.remove('./one.ppm')
+    print("\x1b[32mSTDIN STDOUT\x1b[0m COMMAND TEST PASSED")
+
+def test_output_format_command():
+    args = [image_path, '-o', './out.bmp', 'mirror']
+    output = run_rust_cli(args)
+    assert "Mirrored image saved as" in output
+    with open('./out.bmp', 'rb') as image:
+        assert image.read(2) == b'BM'
+    output = run_rust_cli([image_path, '-o', './out.pnm', 'mirror'])
+    assert "Mirrored image saved as" in output
+    with open('./out.pnm', 'rb') as image:
+        assert image.read(2) == b'P6'
+    output = run_rust_cli([image_path, '-o', './out.xyz', 'mirror'])
+    assert "unknown extension" in output
+    print("\x1b[32mOUTPUT FORMAT\x1b[0m COMMAND TEST PASSED")
+
+
+def test_output_options_command():
+    sizes = []
+    for quality in ['10', '95']:
+        output = run_rust_cli([image_path, '-o', './out.jpg', '--quality', quality, 'mirror'])
+        assert "Mirrored image saved as" in output
+        sizes.append(os.path.getsize('./out.jpg'))
+    assert sizes[0] < sizes[1]
+    for lossy in [[], ['--lossy', '--quality', '50']]:
+        output = run_rust_cli([image_path, '-o', './out.webp'] + lossy + ['mirror'])
+        assert "Mirrored image saved as" in output
+        with open('./out.webp', 'rb') as image:
+            sizes.append(os.path.getsize('./out.webp'))
+            assert image.read(16)[12:] == (b'VP8 ' if lossy else b'VP8L')
+    assert sizes[3] < sizes[2]
+    output = run_rust_cli([image_path, '-o', './out.png', '--bit_depth', '16', 'mirror'])
+    assert "Mirrored image saved as" in output
+    with open('./out.png', 'rb') as image:
+        assert image.read(25)[24] == 16
+    output = run_rust_cli([image_path, '-o', './out.jpg', '--bit_depth', '16', 'mirror'])
+    assert "can't be written with 16 bits" in output
+    print("\x1b[32mOUTPUT OPTIONS\x1b[0m COMMAND TEST PASSED")
+
+
+def write_transparent_png(path):
+    """
+    Write 2x1 RGBA PNG with a transparent left pixel and an opaque right one.
+    """
+    def chunk(kind, data):
+        return struct.pack('>I', len(data)) + kind + data + struct.pack('>I', zlib.crc32(kind + data))
+    header = struct.pack('>IIBBBBB', 2, 1, 8, 6, 0, 0, 0)
+    pixels = zlib.compress(bytes([0, 200, 100, 50, 0, 200, 100, 50, 255]))
+    with open(path, 'wb') as image:
+        image.write(b'\x89PNG\r\n\x1a\n' + chunk(b'IHDR', header) + chunk(b'IDAT', pixels) + chunk(b'IEND', b''))
+
+
+def test_alpha_command():
+    write_transparent_png('./alpha.png')
+    for op in ['grayscale', 'monochrome_ugly']:
+        output = subprocess.check_output([current_file_path + '/climp', './alpha.png', '-o', '-', '--format', 'pam', op])
+        assert output[-5] == 0 and output[-1] == 255
+        output = subprocess.check_output([current_file_path + '/climp', './alpha.png', '-o', '-', '--format', 'pam', op, '--background', 'white'])
+        assert output[-5] == 255 and output[-1] == 255
+    print("\x1b[32mALPHA\x1b[0m COMMAND TEST PASSED")
+
+
+def test_high_bit_depth_command():
+    output = run_rust_cli([image_path, '-o', './deep.png', '--bit_depth', '16', 'mirror'])
+    assert "Mirrored image saved as" in output
+    output = run_rust_cli(['./deep.png', '-o', './out.png', 'blur', '-r', '2', 'rotate'])
+    assert "Blurred, Rotated image saved as" in output
+    with open('./out.png', 'rb') as image:
+        assert image.read(25)[24] == 16
+    output = run_rust_cli([image_path, '-o', './out.hdr', 'mirror'])
+    assert "Mirrored image saved as" in output
+    output = run_rust_cli(['./out.hdr', '-o', './out.jpg', '--tone_map', 'aces', 'grayscale'])
+    assert "Grayscale image saved as" in output
+    print("\x1b[32mHIGH BIT DEPTH\x1b[0m COMMAND TEST PASSED")
+
+
+def test_crop_command():
+    output = run_rust_cli([image_path, '-o', './out.png', 'crop', '--region', '0,0,50%,10'])
+    assert "Cropped image saved as" in output
+    output = run_rust_cli([image_path, '-o', './out.png', 'crop', '--aspect', '1:1', '--gravity', 'southeast'])
+    assert "Cropped image saved as" in output
+    with open('./out.png', 'rb') as image:
+        width, height = struct.unpack('>II', image.read(24)[16:24])
+        assert width == height
+    output = run_rust_cli([image_path, '-o', './out.png', 'crop', '--region', '0,0,100%,100000'])
+    assert "falls outside of the" in output
+    print("\x1b[32mCROP\x1b[0m COMMAND TEST PASSED")
+
+
+def test_trim_command():
+    write_transparent_png('./alpha.png')
+    output = run_rust_cli(['./alpha.png', '-o', './out.png', 'trim'])
+    assert "Trimmed 2x1 image to 1x1 at 1,0" in output
+    assert "Trimmed image saved as" in output
+    print("\x1b[32mTRIM\x1b[0m COMMAND TEST PASSED")
+
+
+def test_pad_command():
+    output = run_rust_cli([image_path, '-o', './out.png', 'pad', '--aspect', '1:1', '--fill', 'blur', '--blur_radius', '3'])
+    assert "Padded image saved as" in output
+    with open('./out.png', 'rb') as image:
+        width, height = struct.unpack('>II', image.read(24)[16:24])
+        assert width == height
+    output = run_rust_cli([image_path, '-o', './out.png', 'border', '-w', '2', '--color', 'white'])
+    assert "Bordered image saved as" in output
+    for amount in [['--by', '4294967295'], ['--aspect', '1e30'], ['--size', '4000000000x1']]:
+        result = subprocess.run([current_file_path + '/climp', image_path, '-o', './out.png', 'pad'] + amount, capture_output=True, text=True)
+        assert result.returncode == 2
+        assert "image too large" in result.stderr
+    print("\x1b[32mPAD\x1b[0m COMMAND TEST PASSED")
+
+
+def test_adjust_command():
+    output = run_rust_cli([image_path, '-o', './out.png', 'adjust', '--brightness', '-20', '--contrast', '30', '--gamma', '1.2', '--exposure', '0.5'])
+    assert "Adjusted image saved as" in output
+    write_ramp('./ramp.ppm')
+    assert pixels_of(['./ramp.ppm', 'adjust', '--gamma', '1']) == pixels_of(['./ramp.ppm', 'mirror', 'mirror'])
+    os.remove('./ramp.ppm')
+    output = run_rust_cli([image_path, '-o', './out.png', 'adjust', '--gamma', '0'])
+    assert "is not a positive number" in output
+    print("\x1b[32mADJUST\x1b[0m COMMAND TEST PASSED")
+
+
+def test_equalize_command():
+    output = run_rust_cli([image_path, '-o', './out.png', 'equalize'])
+    assert "Equalized image saved as" in output
+    output = run_rust_cli([image_path, '-o', './out.png', 'clahe', '--tile_size', '16', '--clip_limit', '3'])
+    assert "Equalized image saved as" in output
+    output = run_rust_cli([image_path, 'ascii', '-v', '--width', '20', '--equalize', 'clahe'])
+    assert len(output.splitlines()) > 1 and "error" not in output
+    output = run_rust_cli([image_path, '-o', './out.png', 'clahe', '--clip_limit', '0.5'])
+    assert "is not a number from 1 up" in output
+    print("\x1b[32mEQUALIZE\x1b[0m COMMAND TEST PASSED")
+
+
+def test_hsl_command():
+    output = run_rust_cli([image_path, '-o', './out.png', 'hsl', '--hue', '-90', '--saturation', '120%', '--lightness', '0.9'])
+    assert "Adjusted image saved as" in output
+    output = run_rust_cli([image_path, '-o', './out.png', 'hsl', '--saturation', '0', '--hue_range', 'green'])
+    assert "Adjusted image saved as" in output
+    output = run_rust_cli([image_path, '-o', './out.png', 'hsl', '--hue', '30', '--hue_range', 'greenish'])
+    assert "is neither from,to degrees nor a color name" in output
+    write_ramp('./ramp.ppm')
+    original = pixels_of(['./ramp.ppm', 'mirror', 'mirror'])
+    for hue in ['0', '360']:
+        assert pixels_of(['./ramp.ppm', 'hsl', '--hue', hue]) == original
+    # colors away from the range are left exactly as they were
+    ranged = pixels_of(['./ramp.ppm', 'hsl', '--hue', '30', '--hue_range', '200,210', '--feather', '0'])
+    start = original.index(b'ENDHDR\n') + 7
+    changed = 0
+    for i in range(start, len(original), 4):
+        r, g, b = (c / 255 for c in original[i:i + 3])
+        h, _, _ = colorsys.rgb_to_hls(r, g, b)
+        if ranged[i:i + 4] != original[i:i + 4]:
+            changed += 1
+            assert 195 <= h * 360 <= 215
+    assert changed > 0
+    os.remove('./ramp.ppm')
+    print("\x1b[32mHSL\x1b[0m COMMAND TEST PASSED")
+
+
+def test_lut_command():
+    with open('./invert.cube', 'w') as cube:
+        cube.write('TITLE "Invert"\nLUT_3D_SIZE 2\n')
+        for b in range(2):
+            for g in range(2):
+                for r in range(2):
+                    cube.write(f"{1 - r} {1 - g} {1 - b}\n")
+    output = run_rust_cli([image_path, '-o', './out.png', 'lut', '--cube', './invert.cube', '--interpolation', 'tetrahedral', '--strength', '60%'])
+    assert "Graded image saved as" in output
+    with open('./recipe.toml', 'w') as recipe:
+        recipe.write(f'inputs = ["{image_path}"]\n\n[output]\npath = "./recipe.png"\n\n[[steps]]\nop = "lut"\ncube = "./invert.cube"\nstrength = "50%"\n')
+    output = run_rust_cli(['run', './recipe.toml'])
+    assert "Graded image saved as" in output
+    with open('./invert.cube', 'w') as cube:
+        cube.write('LUT_1D_SIZE 2\n1 1 1\n')
+    output = run_rust_cli([image_path, '-o', './out.png', 'lut', '--cube', './invert.cube'])
+    assert "expected 2 table entries, found 1" in output
+    os.remove('./invert.cube')
+    print("\x1b[32mLUT\x1b[0m COMMAND TEST PASSED")
+
+
+def test_luminance_command():
+    for mode in ['average', 'rec601', 'rec709', 'lightness', 'green', 'lab']:
+        output = run_rust_cli([image_path, '-o', './out.png', 'grayscale', '--luminance', mode])
+        assert "Grayscale image saved as" in output
+    output = run_rust_cli([image_path, '-o', './out.png', 'monochrome_ugly', '--luminance', 'lab'])
+    assert "Monochrome image saved as" in output
+    output = run_rust_cli([image_path, 'ascii', '-v', '--width', '20', '--luminance', 'rec601'])
+    assert len(output.splitlines()) > 1 and "error" not in output
+    print("\x1b[32mLUMINANCE\x1b[0m COMMAND TEST PASSED")
+
+
+def test_dither_command():
+    for method in ['floyd-steinberg', 'atkinson', 'jarvis-judice-ninke', 'sierra', 'bayer2', 'bayer4', 'bayer8']:
+        output = run_rust_cli([image_path, '-o', './out.png', 'dither', '--method', method, '--serpentine'])
+        assert "Dithered image saved as" in output
+    output = run_rust_cli([image_path, '-o', './out.png', 'dither', '--method', 'bayer3'])
+    assert "invalid value" in output
+    print("\x1b[32mDITHER\x1b[0m COMMAND TEST PASSED")
+
+
+def test_threshold_command():
+    output = run_rust_cli([image_path, '-o', './out.png', 'monochrome_ugly', '--threshold', 'auto'])
+    assert "Monochrome image saved as" in output
+    for method in ['mean', 'gaussian']:
+        output = run_rust_cli([image_path, '-o', './out.png', 'monochrome_ugly', '--adaptive', method, '--window', '15', '--offset', '-5'])
+        assert "Monochrome image saved as" in output
+    output = run_rust_cli([image_path, '-o', './out.png', 'monochrome_ugly', '--adaptive', 'mean', '--threshold', '100'])
+    assert "cannot be used with" in output
+    print("\x1b[32mTHRESHOLD\x1b[0m COMMAND TEST PASSED")
+
+
+def test_quantize_command():
+    output = run_rust_cli([image_path, '-o', './out.png', 'quantize', '--colors', '8', '--method', 'k-means', '--dither', 'atkinson'])
+    assert "Quantized image saved as" in output
+    output = run_rust_cli([image_path, '-o', './out.gif', 'pixelate', '-p', '4', 'quantize', '--palette', 'pico-8'])
+    assert "Pixelated, Quantized image saved as" in output
+    with open('./palette.gpl', 'w') as palette:
+        palette.write("GIMP Palette\nName: Test\n#\n255 0 0 Red\n0 0 255 Blue\n")
+    output = run_rust_cli([image_path, '-o', './out.png', 'quantize', '--palette', './palette.gpl', '--dither', 'bayer4'])
+    assert "Quantized image saved as" in output
+    os.remove('./palette.gpl')
+    output = run_rust_cli([image_path, '-o', './out.png', 'quantize', '--palette', 'nope'])
+    assert "neither a built-in palette nor a readable file" in output
+    for method in ['median-cut', 'k-means']:
+        runs = [pixels_of([image_path, 'quantize', '--colors', '6', '--method', method]) for _ in range(3)]
+        assert runs[0] == runs[1] == runs[2]
+    print("\x1b[32mQUANTIZE\x1b[0m COMMAND TEST PASSED")
+
+
+def test_palette_command():
+    output = run_rust_cli(['palette', image_path, '--colors', '4'])
+    assert len(output.splitlines()) == 4 and output.startswith("#")
+    output = run_rust_cli([image_path, 'pixelate', '-p', '8', 'palette', '-c', '3', '--report', 'json', '--method', 'k-means'])
+    assert '"share"' in output
+    output = run_rust_cli(['-o', './palette.gpl', 'palette', image_path, '--report', 'gpl'])
+    assert "Palette of" in output
+    output = run_rust_cli([image_path, '-o', './out.png', 'quantize', '--palette', './palette.gpl'])
+    assert "Quantized image saved as" in output
+    os.remove('./palette.gpl')
+    output = run_rust_cli(['-o', './palette.png', 'palette', image_path, '--report', 'swatch'])
+    assert "Palette of" in output
+    os.remove('./palette.png')
+    shutil.copy(image_path, './photo_copy.jpg')
+    result = subprocess.run([current_file_path + '/climp', 'palette', image_path, './photo_copy.jpg', '-c', '2'], capture_output=True, text=True)
+    assert result.returncode == 2
+    assert "use --out-dir" in result.stderr
+    output = run_rust_cli(['--out-dir', './palettes', 'palette', image_path, './photo_copy.jpg', '-c', '2', '--report', 'json'])
+    assert "Processed 2 files: 2 succeeded, 0 failed" in output
+    assert os.path.exists('./palettes/photo.json') and os.path.exists('./palettes/photo_copy.json')
+    shutil.rmtree('./palettes')
+    result = subprocess.run([current_file_path + '/climp', image_path, './photo_copy.jpg', 'ascii', '-v', '--width', '10'], capture_output=True, text=True)
+    assert "Processed 2 files" in result.stderr and "Processed" not in result.stdout
+    os.remove('./photo_copy.jpg')
+    # the same image always gives the same report
+    for report in ['hex', 'json', 'gpl', 'ase']:
+        for method in ['median-cut', 'k-means']:
+            args = [current_file_path + '/climp', '-o', '-', 'palette', image_path, '-c', '6', '--report', report, '--method', method]
+            runs = [subprocess.run(args, capture_output=True).stdout for _ in range(2)]
+            assert runs[0] and runs[0] == runs[1]
+    print("\x1b[32mPALETTE\x1b[0m COMMAND TEST PASSED")
+
 
 if __name__ == "__main__":
     try:
@@ -81,6 +438,27 @@ if __name__ == "__main__":
         test_grayscale_command()
         test_monochrome_ugly_command()
         test_scale_command()
+        test_pipeline_command()
+        test_batch_command()
+        test_missing_file_exit_code()
+        test_run_recipe_command()
+        test_stdin_stdout_command()
+        test_output_format_command()
+        test_output_options_command()
+        test_alpha_command()
+        test_high_bit_depth_command()
+        test_crop_command()
+        test_trim_command()
+        test_pad_command()
+        test_adjust_command()
+        test_hsl_command()
+        test_lut_command()
+        test_luminance_command()
+        test_dither_command()
+        test_threshold_command()
+        test_quantize_command()
+        test_palette_command()
+        test_equalize_command()
         print()
         print("\x1b[32mAll tests passed!\x1b[0m")
     except AssertionError as e: